@@ -1,9 +1,27 @@
+use crate::{app::{missions::QuadMissionTrait, systems::sys_waypoint::SETPOINT_RATE_HZ}, common::geofence::Geofence};
+
 pub struct AppConfig{
-    
+    /// App loop rate, systems only run when ticked so this caps e.g. the setpoint stream
+    pub tick_hz: f32,
+    /// Rate for the full QuadAppState JSON snapshot, capped by the app tick
+    pub state_publish_hz: f32,
+    /// Run in order by SysMissionRunner, add with enqueue_mission
+    pub missions: Vec<Box<dyn QuadMissionTrait + Send>>,
+    /// Waypoints outside it are skipped by WaypointSystem (None: no fence)
+    pub geofence: Option<Geofence>,
 }
 
 impl AppConfig{
     pub fn new() -> Self {
-        Self {  }
+        Self {
+            tick_hz: SETPOINT_RATE_HZ,
+            state_publish_hz: 1.0,
+            missions: Vec::new(),
+            geofence: None,
+        }
+    }
+
+    pub fn enqueue_mission(&mut self, mission: Box<dyn QuadMissionTrait + Send>) {
+        self.missions.push(mission);
     }
 }
\ No newline at end of file
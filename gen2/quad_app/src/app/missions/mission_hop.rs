@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use log::info;
 use mavlink::ardupilotmega::COMMAND_LONG_DATA;
 
@@ -6,84 +8,186 @@ use crate::{
     common::{
         commands::{QuadAppCommand, QuadAppCommandType},
         context::QuadAppContext,
+        health::HealthStatus,
     },
     link::mav_mode::ArduMode,
 };
 
-pub struct MissionHop {}
+/// Takeoff height in meters
+pub const HOP_HEIGHT_M: f32 = 2.0;
+/// Time given to the autopilot to switch to GUIDED before arming
+const MODE_SETTLE: Duration = Duration::from_millis(2000);
+/// Time between arming and the takeoff command
+const ARM_SETTLE: Duration = Duration::from_millis(1000);
+const HEALTH_LOG_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HopStage {
+    WaitHealthy,
+    SetGuided { since: Instant },
+    Armed { since: Instant },
+    Done,
+}
+
+/// Waits for a healthy EKF, then GUIDED, arm and takeoff to HOP_HEIGHT_M.
+/// One stage per tick, so the app loop keeps running while it waits.
+pub struct MissionHop {
+    stage: HopStage,
+    last_health_log: Option<Instant>,
+}
 
 impl MissionHop {
     pub fn new() -> Self {
-        Self {}
+        Self { stage: HopStage::WaitHealthy, last_health_log: None }
     }
-}
 
-impl QuadMissionTrait for MissionHop {
-    fn run(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
-        // Wait for quad health to be ok
-        loop {
-            let health_result = {
-                let state = context.state.read().unwrap();
-                state.ekf_status.is_healthy()
-            };
-
-            if let Err(e) = health_result {
-                log::warn!("MissionHop // Waiting for quad health to be ok: {}", e);
-                std::thread::sleep(std::time::Duration::from_millis(500));
-            } else {
-                break;
+    fn tick_at(&mut self, context: &QuadAppContext, now: Instant) {
+        match self.stage {
+            HopStage::WaitHealthy => self.tick_wait_healthy(context, now),
+            HopStage::SetGuided { since } => {
+                if now.duration_since(since) < MODE_SETTLE {
+                    return;
+                }
+                info!("MissionHop // Arming quad");
+                // Arm the quad
+                let arm_cmd = mavlink::ardupilotmega::MavMessage::COMMAND_LONG(COMMAND_LONG_DATA {
+                    param1: 1.0,
+                    param2: 21196., // 21196 is the code for arm/disarm forcefully
+                    command: mavlink::ardupilotmega::MavCmd::MAV_CMD_COMPONENT_ARM_DISARM,
+                    ..Default::default()
+                });
+                push_command(context, arm_cmd);
+                info!("MissionHop // Waiting {}s then taking off", ARM_SETTLE.as_secs_f32());
+                self.stage = HopStage::Armed { since: now };
+            }
+            HopStage::Armed { since } => {
+                if now.duration_since(since) < ARM_SETTLE {
+                    return;
+                }
+                info!("MissionHop // Taking off to {}m", HOP_HEIGHT_M);
+                let takeoff_cmd = mavlink::ardupilotmega::MavMessage::COMMAND_LONG(COMMAND_LONG_DATA {
+                    param3: 5.0,
+                    param7: HOP_HEIGHT_M,
+                    command: mavlink::ardupilotmega::MavCmd::MAV_CMD_NAV_TAKEOFF,
+                    ..Default::default()
+                });
+                push_command(context, takeoff_cmd);
+                self.stage = HopStage::Done;
             }
+            HopStage::Done => {}
+        }
+    }
+
+    fn tick_wait_healthy(&mut self, context: &QuadAppContext, now: Instant) {
+        let (health_status, health_result) = {
+            let state = context.state.read().unwrap();
+            (state.health_status, state.ekf_status.is_healthy())
+        };
+        if health_status != HealthStatus::Healthy {
+            // Waiting is expected for a while after boot, keep the log readable
+            if self.last_health_log.is_none_or(|last| now.duration_since(last) >= HEALTH_LOG_INTERVAL) {
+                match health_result {
+                    Err(e) => log::warn!("MissionHop // Waiting for quad health to be ok: {}", e),
+                    Ok(()) => log::warn!("MissionHop // Waiting for quad health to settle: {:?}", health_status),
+                }
+                self.last_health_log = Some(now);
+            }
+            return;
         }
         log::info!("MissionHop // Quad health is ok");
         log::info!("MissionHop // Setting mode to GUIDED");
-        // Set the mode to AUTO
-        {
-            let mode_msg = ArduMode::Guided.build_mode_message();
-            context.commands.lock().unwrap().push_back(QuadAppCommand::new(
-                QuadAppCommandType::MavlinkRaw(mode_msg.unwrap()),
-            ));
+        if let Some(mode_msg) = ArduMode::Guided.build_mode_message() {
+            push_command(context, mode_msg);
         }
+        self.stage = HopStage::SetGuided { since: now };
+    }
+}
 
-        // Wait 2s to allow the mode to set
-        std::thread::sleep(std::time::Duration::from_millis(2000));
-        info!("MissionHop // Arming quad");
-        // Arm the quad
-        {
-            let arm_cmd = mavlink::ardupilotmega::MavMessage::COMMAND_LONG(
-                mavlink::ardupilotmega::COMMAND_LONG_DATA {
-                    param1: 1.0,
-                    param2: 21196., // 21196 is the code for arm/disarm forcefully
-                    param3: 0.0,
-                    param4: 0.0,
-                    param5: 0.0,
-                    param6: 0.0,
-                    param7: 0.0,
-                    command: mavlink::ardupilotmega::MavCmd::MAV_CMD_COMPONENT_ARM_DISARM,
-                    target_system: 0,
-                    target_component: 0,
-                    confirmation: 0,
-                },
-            );
-            context
-                .commands
-                .lock()
-                .unwrap()
-                .push_back(QuadAppCommand::new(QuadAppCommandType::MavlinkRaw(arm_cmd)));
-        }
-        info!("MissionHop // Waiting 1s then taking off");
-        // Wait 1s then take off
-        {
-            let height = 2.0;
-            let takeoff_cmd = mavlink::ardupilotmega::MavMessage::COMMAND_LONG(COMMAND_LONG_DATA {
-                param3: 5.0,
-                param7: height,
-                command: mavlink::ardupilotmega::MavCmd::MAV_CMD_NAV_TAKEOFF,
-                ..Default::default()
-            });
-            context.commands.lock().unwrap().push_back(QuadAppCommand::new(
-                QuadAppCommandType::MavlinkRaw(takeoff_cmd),
-            ));
-        }
+fn push_command(context: &QuadAppContext, msg: mavlink::ardupilotmega::MavMessage) {
+    context
+        .commands
+        .lock()
+        .unwrap()
+        .push_back(QuadAppCommand::new(QuadAppCommandType::MavlinkRaw(msg)));
+}
+
+impl QuadMissionTrait for MissionHop {
+    fn run(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        info!("MissionHop // Starting, waiting for quad health");
+        self.stage = HopStage::WaitHealthy;
+        self.last_health_log = None;
+        Ok(())
+    }
+
+    fn tick(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        self.tick_at(context, Instant::now());
         Ok(())
     }
+
+    fn is_complete(&self) -> bool {
+        self.stage == HopStage::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::log_rerun::RerunSink;
+
+    fn sent_commands(context: &QuadAppContext) -> Vec<mavlink::ardupilotmega::MavCmd> {
+        context
+            .commands
+            .lock()
+            .unwrap()
+            .drain(..)
+            .map(|command| match command.cmd_type {
+                QuadAppCommandType::MavlinkRaw(mavlink::ardupilotmega::MavMessage::COMMAND_LONG(data)) => data.command,
+                other => panic!("Unexpected command {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn waits_for_health_without_blocking() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut mission = MissionHop::new();
+        mission.run(&context).unwrap();
+        let t0 = Instant::now();
+        for i in 0..10 {
+            mission.tick_at(&context, t0 + Duration::from_secs(i));
+        }
+        assert_eq!(mission.stage, HopStage::WaitHealthy);
+        assert!(!mission.is_complete());
+        assert!(sent_commands(&context).is_empty());
+    }
+
+    #[test]
+    fn sets_guided_then_arms_then_takes_off() {
+        use mavlink::ardupilotmega::MavCmd;
+
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        context.state.write().unwrap().health_status = HealthStatus::Healthy;
+        let mut mission = MissionHop::new();
+        mission.run(&context).unwrap();
+        let t0 = Instant::now();
+
+        mission.tick_at(&context, t0);
+        assert_eq!(sent_commands(&context), vec![MavCmd::MAV_CMD_DO_SET_MODE]);
+
+        mission.tick_at(&context, t0 + MODE_SETTLE - Duration::from_millis(1));
+        assert!(sent_commands(&context).is_empty());
+        mission.tick_at(&context, t0 + MODE_SETTLE);
+        assert_eq!(sent_commands(&context), vec![MavCmd::MAV_CMD_COMPONENT_ARM_DISARM]);
+
+        let armed = t0 + MODE_SETTLE;
+        mission.tick_at(&context, armed + ARM_SETTLE - Duration::from_millis(1));
+        assert!(sent_commands(&context).is_empty());
+        assert!(!mission.is_complete());
+        mission.tick_at(&context, armed + ARM_SETTLE);
+        assert_eq!(sent_commands(&context), vec![MavCmd::MAV_CMD_NAV_TAKEOFF]);
+        assert!(mission.is_complete());
+
+        mission.tick_at(&context, armed + ARM_SETTLE * 10);
+        assert!(sent_commands(&context).is_empty());
+    }
 }
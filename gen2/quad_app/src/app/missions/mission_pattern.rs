@@ -0,0 +1,109 @@
+use log::info;
+
+use crate::{
+    app::{
+        missions::QuadMissionTrait,
+        patterns::{PatternConfig, QuadPatternTrait},
+    },
+    common::context::QuadAppContext,
+};
+
+/// Generates a pattern and hands it to WaypointSystem, complete once the path has been flown
+pub struct MissionPattern {
+    pattern: Box<dyn QuadPatternTrait + Send>,
+    config: PatternConfig,
+    queued: bool,
+    done: bool,
+}
+
+impl MissionPattern {
+    pub fn new(pattern: Box<dyn QuadPatternTrait + Send>, config: PatternConfig) -> Self {
+        Self { pattern, config, queued: false, done: false }
+    }
+}
+
+impl QuadMissionTrait for MissionPattern {
+    fn run(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        let waypoints = self.pattern.generate(context, self.config.clone())?;
+        info!("MissionPattern // Queueing {} waypoints", waypoints.len());
+        context.waypoint_paths.lock().unwrap().push_back(waypoints);
+        self.queued = true;
+        self.done = false;
+        Ok(())
+    }
+
+    fn tick(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        if !self.queued || self.done {
+            return Ok(());
+        }
+        // WaypointSystem pulls the path and marks it active in the same tick
+        let waiting = !context.waypoint_paths.lock().unwrap().is_empty();
+        let flying = context.state.read().unwrap().path_active;
+        if !waiting && !flying {
+            info!("MissionPattern // Path done");
+            self.done = true;
+        }
+        Ok(())
+    }
+
+    fn is_complete(&self) -> bool {
+        self.done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{
+            patterns::PatternKind,
+            systems::{AppSystemTrait, sys_waypoint::WaypointSystem},
+        },
+        common::{log_rerun::RerunSink, state::NED},
+    };
+
+    #[test]
+    fn queues_the_pattern_and_completes_once_it_was_flown() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut waypoint_system = WaypointSystem::new(None);
+        let config = PatternConfig::new(NED::new(0.0, 0.0, -2.0), 4.0, 0.0);
+        let mut mission = MissionPattern::new(PatternKind::Circle.build(), config);
+
+        mission.run(&context).unwrap();
+        assert_eq!(context.waypoint_paths.lock().unwrap().front().map(Vec::len), Some(8));
+        mission.tick(&context).unwrap();
+        assert!(!mission.is_complete());
+
+        // Picked up by WaypointSystem
+        waypoint_system.tick(&context).unwrap();
+        assert!(context.waypoint_paths.lock().unwrap().is_empty());
+        assert!(context.state.read().unwrap().path_active);
+        mission.tick(&context).unwrap();
+        assert!(!mission.is_complete());
+
+        // Fly it by putting the vehicle on each waypoint in turn
+        let waypoints = PatternKind::Circle.build().generate(&context, PatternConfig::new(NED::new(0.0, 0.0, -2.0), 4.0, 0.0)).unwrap();
+        for waypoint in waypoints {
+            context.state.write().unwrap().ned_current = waypoint.ned;
+            // COMMAND -> TRANSIT -> COMPLETE -> HOLD -> next waypoint (or disabled after the last)
+            for _ in 0..4 {
+                waypoint_system.tick(&context).unwrap();
+            }
+            if context.state.read().unwrap().path_active {
+                mission.tick(&context).unwrap();
+                assert!(!mission.is_complete());
+            }
+        }
+        assert!(!context.state.read().unwrap().path_active);
+        mission.tick(&context).unwrap();
+        assert!(mission.is_complete());
+    }
+
+    #[test]
+    fn failed_generation_fails_run() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut mission = MissionPattern::new(PatternKind::Grid.build(), PatternConfig::new(NED::default(), 0.0, 0.0));
+        assert!(mission.run(&context).is_err());
+        assert!(context.waypoint_paths.lock().unwrap().is_empty());
+    }
+}
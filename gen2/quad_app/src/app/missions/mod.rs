@@ -3,7 +3,20 @@ use crate::common::context::QuadAppContext;
 
 
 pub mod mission_hop;
+pub mod mission_pattern;
 
+/// Missions run on the app thread, so neither `run` nor `tick` may block
 pub trait QuadMissionTrait{
+    /// Called once when SysMissionRunner starts the mission
     fn run(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error>;
-}
\ No newline at end of file
+
+    /// Called every app tick after run, until is_complete
+    fn tick(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// Polled by SysMissionRunner after every tick, one-shot missions are complete once run returns
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
@@ -1,8 +1,8 @@
 use std::{thread, time::Duration};
 
-use log::{error, info};
+use log::{error, info, warn};
 
-use crate::{app::{app_config::AppConfig, systems::{AppSystemTrait, sys_mission_runner::SysMissionRunner, sys_waypoint::WaypointSystem}}, common::context::QuadAppContext};
+use crate::{app::{app_config::AppConfig, systems::{AppSystemTrait, sys_led::SysLed, sys_mission_runner::SysMissionRunner, sys_state_publish::SysStatePublish, sys_waypoint::{SETPOINT_RATE_HZ, WaypointSystem}}}, common::{context::QuadAppContext, led::{LED, LedAnimation}}};
 
 pub mod systems;
 pub mod missions;
@@ -23,19 +23,49 @@ impl QuadApp{
     pub fn start(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
         info!("QuadApp // Starting");
         let context = context.clone();
+        if !self.config.tick_hz.is_finite() || self.config.tick_hz <= 0.0 {
+            return Err(anyhow::anyhow!("QuadApp // tick_hz must be positive, got {}", self.config.tick_hz));
+        }
+        if self.config.tick_hz < SETPOINT_RATE_HZ {
+            warn!(
+                "QuadApp // Ticking at {}Hz, setpoints will only stream at that rate instead of {}Hz",
+                self.config.tick_hz, SETPOINT_RATE_HZ
+            );
+        }
+        let tick_period = Duration::from_secs_f32(1.0 / self.config.tick_hz);
+        let mut state_publish = SysStatePublish::new(self.config.state_publish_hz)?;
+        let missions = std::mem::take(&mut self.config.missions);
+        let geofence = self.config.geofence.clone();
         let app_thread_handle = std::thread::spawn(move || {
 
 
-                let mut waypoint_system = WaypointSystem::new();
-                let mut mission_runner = SysMissionRunner::new();
+                let mut waypoint_system = WaypointSystem::new(geofence);
+                let mut mission_runner = SysMissionRunner::new(missions);
+                let mut led = SysLed::new();
+
+                // Show colour follows the waypoint that was just reached
+                waypoint_system.on_waypoint_reached(Box::new(|context, event| {
+                    info!("QuadApp // Reached waypoint {}", event.index);
+                    context.state.write().unwrap().led_state = LED::new(event.waypoint.color, 1.0, true, LedAnimation::Solid);
+                }));
 
                 waypoint_system.start(&context).unwrap();
                 mission_runner.start(&context).unwrap();
+                state_publish.start(&context).unwrap();
+                led.start(&context).unwrap();
             loop {
                 let result = waypoint_system.tick(&context);
-                let result = mission_runner.tick(&context);
+                if let Err(e) = mission_runner.tick(&context) {
+                    error!("QuadApp // Mission failed: {}", e);
+                }
+                if let Err(e) = state_publish.tick(&context) {
+                    error!("QuadApp // State publish failed: {}", e);
+                }
+                if let Err(e) = led.tick(&context) {
+                    error!("QuadApp // LED update failed: {}", e);
+                }
               
-                thread::sleep(Duration::from_millis(250));
+                thread::sleep(tick_period);
             }
         });
         app_thread_handle.join().map_err(|e| anyhow::anyhow!("App thread panicked: {:?}", e))?;
@@ -1,5 +1,18 @@
-use crate::common::{context::QuadAppContext, state::NED, waypoint::Waypoint};
+use clap::ValueEnum;
 
+use crate::{
+    app::patterns::{pattern_circle::CirclePattern, pattern_grid::GridPattern},
+    common::{context::QuadAppContext, state::NED, waypoint::Waypoint},
+};
+
+pub mod pattern_circle;
+pub mod pattern_grid;
+
+/// Arrival radius given to generated waypoints
+pub const PATTERN_ARRIVAL_RADIUS_M: f32 = 0.5;
+pub const PATTERN_COLOR: [u8; 3] = [255, 255, 255];
+const CIRCLE_POINTS: usize = 8;
+const GRID_LANES: usize = 4;
 
 #[derive(Default, Debug, Clone)]
 pub struct PatternConfig{
@@ -11,7 +24,52 @@ impl PatternConfig{
     pub fn new(center_ned: NED, scale: f32, hold_time: f32) -> Self {
         Self { center_ned, scale, hold_time }
     }
+
+    /// Scale must be positive, hold time zero or more, both finite (NaN fails too)
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if !self.scale.is_finite() || self.scale <= 0.0 {
+            return Err(anyhow::anyhow!("Size must be positive, got {}", self.scale));
+        }
+        if !self.hold_time.is_finite() || self.hold_time < 0.0 {
+            return Err(anyhow::anyhow!("Hold time must not be negative, got {}", self.hold_time));
+        }
+        Ok(())
+    }
 }
 pub trait QuadPatternTrait{
     fn generate(&mut self, context: &QuadAppContext, config: PatternConfig) -> Result<Vec<Waypoint>, anyhow::Error>;
-}
\ No newline at end of file
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    /// CirclePattern, 8 points
+    Circle,
+    /// GridPattern, 4 lanes
+    Grid,
+}
+
+impl PatternKind {
+    pub fn build(self) -> Box<dyn QuadPatternTrait + Send> {
+        match self {
+            PatternKind::Circle => Box::new(CirclePattern::new(CIRCLE_POINTS)),
+            PatternKind::Grid => Box::new(GridPattern::new(GRID_LANES)),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_nan_and_out_of_range_values() {
+        let config = |scale: f32, hold_time: f32| PatternConfig::new(NED::default(), scale, hold_time);
+        assert!(config(4.0, 0.0).validate().is_ok());
+        assert!(config(4.0, 1.5).validate().is_ok());
+        assert!(config(f32::NAN, 1.0).validate().is_err());
+        assert!(config(f32::INFINITY, 1.0).validate().is_err());
+        assert!(config(-1.0, 1.0).validate().is_err());
+        assert!(config(4.0, f32::NAN).validate().is_err());
+        assert!(config(4.0, f32::INFINITY).validate().is_err());
+        assert!(config(4.0, -0.5).validate().is_err());
+    }
+}
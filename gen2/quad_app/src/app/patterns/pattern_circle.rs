@@ -0,0 +1,83 @@
+use std::f32::consts::PI;
+
+use crate::{
+    app::patterns::{PATTERN_ARRIVAL_RADIUS_M, PATTERN_COLOR, PatternConfig, QuadPatternTrait},
+    common::{context::QuadAppContext, state::NED, waypoint::Waypoint},
+};
+
+/// `points` evenly spaced waypoints on a circle of radius `scale` around `center_ned`,
+/// at the center's altitude, yawed to face the center
+pub struct CirclePattern {
+    pub points: usize,
+}
+
+impl CirclePattern {
+    pub fn new(points: usize) -> Self {
+        Self { points }
+    }
+}
+
+impl QuadPatternTrait for CirclePattern {
+    fn generate(&mut self, _context: &QuadAppContext, config: PatternConfig) -> Result<Vec<Waypoint>, anyhow::Error> {
+        if self.points == 0 {
+            return Err(anyhow::anyhow!("CirclePattern // Needs at least one point"));
+        }
+        config.validate().map_err(|e| anyhow::anyhow!("CirclePattern // {}", e))?;
+
+        let center = &config.center_ned;
+        let waypoints = (0..self.points)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f32 / self.points as f32;
+                let ned = NED::new(
+                    center.north + config.scale * angle.cos(),
+                    center.east + config.scale * angle.sin(),
+                    center.down,
+                );
+                let yaw_deg = (angle + PI).to_degrees().rem_euclid(360.0);
+                Waypoint::new(ned, PATTERN_COLOR, config.hold_time, PATTERN_ARRIVAL_RADIUS_M, yaw_deg, i as u32)
+            })
+            .collect();
+        Ok(waypoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::log_rerun::RerunSink;
+
+    fn generate(points: usize, scale: f32) -> Result<Vec<Waypoint>, anyhow::Error> {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        CirclePattern::new(points).generate(&context, PatternConfig::new(NED::new(10.0, -5.0, -3.0), scale, 1.5))
+    }
+
+    #[test]
+    fn points_lie_on_the_circle_facing_the_center() {
+        let center = NED::new(10.0, -5.0, -3.0);
+        let waypoints = generate(8, 4.0).unwrap();
+        assert_eq!(waypoints.len(), 8);
+        for (i, waypoint) in waypoints.iter().enumerate() {
+            assert!((waypoint.ned.distance(&center) - 4.0).abs() < 1e-4);
+            assert_eq!(waypoint.ned.down, center.down);
+            assert_eq!(waypoint.segment_id, i as u32);
+            assert_eq!(waypoint.hold_time, 1.5);
+            assert_eq!(waypoint.arrival_radius, PATTERN_ARRIVAL_RADIUS_M);
+            assert!((0.0..360.0).contains(&waypoint.yaw_deg));
+            // Heading along yaw from the waypoint ends up at the center
+            let yaw = waypoint.yaw_deg.to_radians();
+            assert!((waypoint.ned.north + 4.0 * yaw.cos() - center.north).abs() < 1e-3);
+            assert!((waypoint.ned.east + 4.0 * yaw.sin() - center.east).abs() < 1e-3);
+        }
+        // First point due north of the center
+        assert!((waypoints[0].ned.north - 14.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rejects_no_points_or_non_positive_radius() {
+        assert!(generate(0, 4.0).is_err());
+        assert!(generate(8, 0.0).is_err());
+        assert!(generate(8, -1.0).is_err());
+        assert!(generate(8, f32::NAN).is_err());
+        assert!(generate(8, f32::INFINITY).is_err());
+    }
+}
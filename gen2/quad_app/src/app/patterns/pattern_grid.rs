@@ -0,0 +1,92 @@
+use crate::{
+    app::patterns::{PATTERN_ARRIVAL_RADIUS_M, PATTERN_COLOR, PatternConfig, QuadPatternTrait},
+    common::{context::QuadAppContext, state::NED, waypoint::Waypoint},
+};
+
+/// Boustrophedon (lawnmower) path over a `scale` x `scale` square centered on `center_ned`.
+/// Each of the `lanes` lanes runs north-south, alternating direction, lanes are spaced east.
+pub struct GridPattern {
+    pub lanes: usize,
+}
+
+impl GridPattern {
+    pub fn new(lanes: usize) -> Self {
+        Self { lanes }
+    }
+}
+
+impl QuadPatternTrait for GridPattern {
+    fn generate(&mut self, _context: &QuadAppContext, config: PatternConfig) -> Result<Vec<Waypoint>, anyhow::Error> {
+        if self.lanes < 2 {
+            return Err(anyhow::anyhow!("GridPattern // Needs at least two lanes, got {}", self.lanes));
+        }
+        config.validate().map_err(|e| anyhow::anyhow!("GridPattern // {}", e))?;
+
+        let center = &config.center_ned;
+        let half = config.scale / 2.0;
+        let lane_spacing = config.scale / (self.lanes - 1) as f32;
+        let mut waypoints = Vec::with_capacity(self.lanes * 2);
+        for lane in 0..self.lanes {
+            let east = center.east - half + lane as f32 * lane_spacing;
+            let (start_north, end_north) = if lane % 2 == 0 { (-half, half) } else { (half, -half) };
+            // Face along the lane
+            let yaw_deg = if lane % 2 == 0 { 0.0 } else { 180.0 };
+            for north in [start_north, end_north] {
+                let ned = NED::new(center.north + north, east, center.down);
+                waypoints.push(Waypoint::new(
+                    ned,
+                    PATTERN_COLOR,
+                    config.hold_time,
+                    PATTERN_ARRIVAL_RADIUS_M,
+                    yaw_deg,
+                    lane as u32,
+                ));
+            }
+        }
+        Ok(waypoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::log_rerun::RerunSink;
+
+    fn generate(lanes: usize, scale: f32) -> Result<Vec<Waypoint>, anyhow::Error> {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        GridPattern::new(lanes).generate(&context, PatternConfig::new(NED::new(10.0, -5.0, -3.0), scale, 0.0))
+    }
+
+    #[test]
+    fn two_waypoints_per_lane_inside_the_square() {
+        let waypoints = generate(4, 6.0).unwrap();
+        assert_eq!(waypoints.len(), 8);
+        for waypoint in &waypoints {
+            assert!((7.0 - 1e-4..=13.0 + 1e-4).contains(&waypoint.ned.north));
+            assert!((-8.0 - 1e-4..=-2.0 + 1e-4).contains(&waypoint.ned.east));
+            assert_eq!(waypoint.ned.down, -3.0);
+        }
+        // Lanes span the full width, west to east
+        assert!((waypoints[0].ned.east + 8.0).abs() < 1e-4);
+        assert!((waypoints[7].ned.east + 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn lanes_alternate_direction() {
+        let waypoints = generate(3, 6.0).unwrap();
+        for (lane, pair) in waypoints.chunks(2).enumerate() {
+            assert_eq!(pair[0].ned.east, pair[1].ned.east);
+            assert_eq!(pair[0].segment_id, lane as u32);
+            let heading_north = pair[1].ned.north > pair[0].ned.north;
+            assert_eq!(heading_north, lane % 2 == 0);
+            assert_eq!(pair[0].yaw_deg, if heading_north { 0.0 } else { 180.0 });
+        }
+    }
+
+    #[test]
+    fn rejects_too_few_lanes_or_non_positive_size() {
+        assert!(generate(1, 6.0).is_err());
+        assert!(generate(4, 0.0).is_err());
+        assert!(generate(4, f32::NAN).is_err());
+    }
+}
@@ -2,6 +2,8 @@ use crate::common::context::QuadAppContext;
 
 pub mod sys_waypoint;
 pub mod sys_mission_runner;
+pub mod sys_state_publish;
+pub mod sys_led;
 
 pub trait AppSystemTrait{
     fn start(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error>;
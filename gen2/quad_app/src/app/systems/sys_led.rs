@@ -0,0 +1,79 @@
+use log::info;
+
+use crate::{
+    app::systems::AppSystemTrait,
+    common::{
+        commands::{QuadAppCommand, QuadAppCommandType},
+        context::QuadAppContext,
+        led::LED,
+    },
+    link::mav_builders::build_led_control,
+};
+
+/// Sends state.led_state to the aircraft whenever it changes
+pub struct SysLed {
+    last_sent: Option<LED>,
+}
+
+impl SysLed {
+    pub fn new() -> Self {
+        Self { last_sent: None }
+    }
+}
+
+impl AppSystemTrait for SysLed {
+    fn start(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    fn tick(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        let led_state = context.state.read().unwrap().led_state.clone();
+        if self.last_sent.as_ref() == Some(&led_state) {
+            return Ok(());
+        }
+
+        info!("SysLed // LED -> {:?} {:?}", led_state.output_rgb(), led_state.animation);
+        context.commands.lock().unwrap().push_back(QuadAppCommand::new(
+            QuadAppCommandType::MavlinkRaw(build_led_control(&led_state)),
+        ));
+        self.last_sent = Some(led_state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{led::LedAnimation, log_rerun::RerunSink};
+
+    fn sent(context: &QuadAppContext) -> usize {
+        context.commands.lock().unwrap().drain(..).count()
+    }
+
+    #[test]
+    fn one_command_per_change() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut led = SysLed::new();
+
+        // Initial state goes out once
+        led.tick(&context).unwrap();
+        led.tick(&context).unwrap();
+        assert_eq!(sent(&context), 1);
+
+        context.state.write().unwrap().led_state = LED::new([255, 0, 0], 1.0, true, LedAnimation::Solid);
+        for _ in 0..10 {
+            led.tick(&context).unwrap();
+        }
+        assert_eq!(sent(&context), 1);
+
+        // Same colour written again is not a change
+        context.state.write().unwrap().led_state = LED::new([255, 0, 0], 1.0, true, LedAnimation::Solid);
+        led.tick(&context).unwrap();
+        assert_eq!(sent(&context), 0);
+
+        context.state.write().unwrap().led_state = LED::new([255, 0, 0], 1.0, true, LedAnimation::Blink { rate_hz: 2 });
+        led.tick(&context).unwrap();
+        led.tick(&context).unwrap();
+        assert_eq!(sent(&context), 1);
+    }
+}
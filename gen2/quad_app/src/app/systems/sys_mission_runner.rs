@@ -1,22 +1,111 @@
-use crate::{app::{missions::{QuadMissionTrait, mission_hop::MissionHop}, systems::AppSystemTrait}, common::context::QuadAppContext};
+use std::collections::VecDeque;
 
+use log::info;
+
+use crate::{app::{missions::QuadMissionTrait, systems::AppSystemTrait}, common::context::QuadAppContext};
+
+/// Runs queued missions one after another, the next one starts once the current one is complete
 pub struct SysMissionRunner{
-    pub mission: Box<dyn QuadMissionTrait>,
+    queue: VecDeque<Box<dyn QuadMissionTrait + Send>>,
+    current: Option<Box<dyn QuadMissionTrait + Send>>,
 }
 
 impl SysMissionRunner{
-    pub fn new() -> Self {
-        Self { mission: Box::new(MissionHop::new()) }
+    pub fn new(missions: Vec<Box<dyn QuadMissionTrait + Send>>) -> Self {
+        Self { queue: missions.into(), current: None }
     }
 }
 
 impl AppSystemTrait for SysMissionRunner{
-    fn start(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
-        self.mission.run(context)?;
+    fn start(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        info!("SysMissionRunner // {} mission(s) queued", self.queue.len());
         Ok(())
     }
     fn tick(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
-       // self.mission.run(context)?;
+        if self.current.is_none() {
+            let Some(mut mission) = self.queue.pop_front() else {
+                return Ok(());
+            };
+            info!("SysMissionRunner // Starting next mission");
+            // A failed mission is dropped so the queue keeps moving
+            mission.run(context)?;
+            self.current = Some(mission);
+        }
+
+        let Some(mission) = &mut self.current else {
+            return Ok(());
+        };
+        if let Err(e) = mission.tick(context) {
+            self.current = None;
+            return Err(e);
+        }
+        if mission.is_complete() {
+            info!("SysMissionRunner // Mission complete, {} remaining", self.queue.len());
+            self.current = None;
+        }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::common::log_rerun::RerunSink;
+
+    /// Records "<name>:run" / "<name>:tick" and completes after `ticks` ticks
+    struct CountingMission {
+        name: &'static str,
+        ticks: usize,
+        fail_run: bool,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl QuadMissionTrait for CountingMission {
+        fn run(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+            self.log.lock().unwrap().push(format!("{}:run", self.name));
+            if self.fail_run {
+                return Err(anyhow::anyhow!("{} failed", self.name));
+            }
+            Ok(())
+        }
+
+        fn tick(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+            self.log.lock().unwrap().push(format!("{}:tick", self.name));
+            self.ticks = self.ticks.saturating_sub(1);
+            Ok(())
+        }
+
+        fn is_complete(&self) -> bool {
+            self.ticks == 0
+        }
+    }
+
+    fn mission(name: &'static str, ticks: usize, fail_run: bool, log: &Arc<Mutex<Vec<String>>>) -> Box<dyn QuadMissionTrait + Send> {
+        Box::new(CountingMission { name, ticks, fail_run, log: log.clone() })
+    }
+
+    #[test]
+    fn ticks_the_current_mission_until_complete_then_starts_the_next() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runner = SysMissionRunner::new(vec![mission("a", 2, false, &log), mission("b", 1, false, &log)]);
+        for _ in 0..5 {
+            runner.tick(&context).unwrap();
+        }
+        assert_eq!(*log.lock().unwrap(), vec!["a:run", "a:tick", "a:tick", "b:run", "b:tick"]);
+        assert!(runner.current.is_none());
+        assert!(runner.queue.is_empty());
+    }
+
+    #[test]
+    fn failed_mission_is_dropped() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut runner = SysMissionRunner::new(vec![mission("a", 1, true, &log), mission("b", 1, false, &log)]);
+        assert!(runner.tick(&context).is_err());
+        runner.tick(&context).unwrap();
+        assert_eq!(*log.lock().unwrap(), vec!["a:run", "b:run", "b:tick"]);
+    }
+}
@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{app::systems::AppSystemTrait, common::{context::QuadAppContext, state::NED}};
+
+/// Points appended to ned_history since the last publish.
+/// Appending `points` at index `start` of the previous history reconstructs the full history;
+/// `start == 0` is a full snapshot and replaces it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NedHistoryDelta {
+    pub start: usize,
+    pub points: Vec<NED>,
+}
+
+/// Logs the whole QuadAppState as JSON at a fixed rate, plus ned_history as deltas
+pub struct SysStatePublish {
+    period: Duration,
+    last_publish: Option<Instant>,
+    /// ned_history length covered by the previous publish
+    ned_history_published: usize,
+    /// ned_history_generation seen by the previous publish
+    ned_history_generation: u64,
+}
+
+impl SysStatePublish {
+    pub fn new(rate_hz: f32) -> Result<Self, anyhow::Error> {
+        if !rate_hz.is_finite() || rate_hz <= 0.0 {
+            return Err(anyhow::anyhow!("SysStatePublish // Rate must be positive, got {}", rate_hz));
+        }
+        Ok(Self {
+            period: Duration::from_secs_f32(1.0 / rate_hz),
+            last_publish: None,
+            ned_history_published: 0,
+            ned_history_generation: 0,
+        })
+    }
+
+    fn ned_history_delta(&mut self, history: &[NED], generation: u64) -> Option<NedHistoryDelta> {
+        // History was cleared since the last publish, start over with a snapshot
+        let cleared = generation != self.ned_history_generation;
+        if cleared {
+            self.ned_history_generation = generation;
+            self.ned_history_published = 0;
+        }
+        if !cleared && history.len() == self.ned_history_published && self.last_publish.is_some() {
+            return None;
+        }
+        let delta = NedHistoryDelta {
+            start: self.ned_history_published,
+            points: history[self.ned_history_published..].to_vec(),
+        };
+        self.ned_history_published = history.len();
+        Some(delta)
+    }
+}
+
+impl AppSystemTrait for SysStatePublish {
+    fn start(&mut self, _context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    fn tick(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
+        let now = Instant::now();
+        if self.last_publish.is_some_and(|last| now.duration_since(last) < self.period) {
+            return Ok(());
+        }
+
+        let state = context.state.read().unwrap();
+        let json = state.to_json()?;
+        let delta = self.ned_history_delta(&state.ned_history, state.ned_history_generation);
+        drop(state);
+        self.last_publish = Some(now);
+
+        let mut log_rerun = context.log_rerun.lock().unwrap();
+        log_rerun.log_json("quad/state", &json);
+        if let Some(delta) = delta {
+            log_rerun.log_json("quad/ned_history", &serde_json::to_string(&delta)?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::state::QuadAppState;
+
+    /// Viewer side: apply a delta on top of the history rebuilt so far
+    fn apply(rebuilt: &mut Vec<NED>, delta: NedHistoryDelta) {
+        rebuilt.truncate(delta.start);
+        rebuilt.extend(delta.points);
+    }
+
+    fn publish(system: &mut SysStatePublish, state: &QuadAppState, rebuilt: &mut Vec<NED>) {
+        if let Some(delta) = system.ned_history_delta(&state.ned_history, state.ned_history_generation) {
+            apply(rebuilt, delta);
+        }
+        system.last_publish = Some(Instant::now());
+        assert_eq!(rebuilt.len(), state.ned_history.len());
+        for (rebuilt, recorded) in rebuilt.iter().zip(&state.ned_history) {
+            assert_eq!(rebuilt.to_array(), recorded.to_array());
+        }
+    }
+
+    #[test]
+    fn deltas_rebuild_the_full_history() {
+        let mut system = SysStatePublish::new(1.0).unwrap();
+        let mut state = QuadAppState::new();
+        let mut rebuilt = Vec::new();
+
+        publish(&mut system, &state, &mut rebuilt);
+        for i in 0..5 {
+            state.record_ned(NED::new(i as f32, 0.0, -2.0));
+        }
+        publish(&mut system, &state, &mut rebuilt);
+        publish(&mut system, &state, &mut rebuilt);
+        for i in 5..8 {
+            state.record_ned(NED::new(i as f32, 0.0, -2.0));
+        }
+        publish(&mut system, &state, &mut rebuilt);
+    }
+
+    #[test]
+    fn clear_is_sent_as_a_snapshot_even_when_the_history_grows_back() {
+        let mut system = SysStatePublish::new(1.0).unwrap();
+        let mut state = QuadAppState::new();
+        let mut rebuilt = Vec::new();
+
+        for i in 0..3 {
+            state.record_ned(NED::new(i as f32, 0.0, -2.0));
+        }
+        publish(&mut system, &state, &mut rebuilt);
+
+        // Cleared and regrown past the published length between two publishes
+        state.clear_ned_history();
+        for i in 0..5 {
+            state.record_ned(NED::new(0.0, i as f32, -2.0));
+        }
+        publish(&mut system, &state, &mut rebuilt);
+
+        // Cleared with nothing recorded since
+        state.clear_ned_history();
+        publish(&mut system, &state, &mut rebuilt);
+    }
+
+    #[test]
+    fn rejects_non_positive_rate() {
+        assert!(SysStatePublish::new(0.0).is_err());
+        assert!(SysStatePublish::new(-1.0).is_err());
+        assert!(SysStatePublish::new(f32::NAN).is_err());
+    }
+}
@@ -1,38 +1,67 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use crate::{app::systems::AppSystemTrait, common::{state::NED, waypoint::Waypoint}};
+use crate::{app::systems::AppSystemTrait, common::{commands::{QuadAppCommand, QuadAppCommandType}, context::QuadAppContext, geofence::Geofence, setpoint_streamer::SetpointStreamer, state::NED, waypoint::Waypoint}};
 
+/// ArduPilot GUIDED expects a steady stream, QuadApp ticks at least this fast by default
+pub const SETPOINT_RATE_HZ: f32 = 20.0;
+const SETPOINT_MAX_SPEED_MPS: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WaypointState{
     HOLD = 0,
     COMMAND = 1,
     TRANSIT = 2,
     COMPLETE = 3, // PReviously Reached
 }
+#[derive(Debug, Clone)]
+pub struct WaypointReachedEvent {
+    /// Position of the waypoint in the path it was pulled from
+    pub index: usize,
+    pub waypoint: Waypoint,
+}
+
+pub type WaypointReachedCallback = Box<dyn FnMut(&QuadAppContext, &WaypointReachedEvent) + Send>;
+
 pub struct WaypointSystem{
     path: Vec<Waypoint>,
     current_waypoint: Option<Waypoint>,
     next_waypoint: Option<Waypoint>,
-    time_start_hold_ms: Option<u64>,
+    hold_start: Option<Instant>,
     state: WaypointState,
     offboard_active: bool,
     last_position_ned: Option<NED>,
     is_enabled: bool,
+    setpoint_streamer: SetpointStreamer,
+    current_index: Option<usize>,
+    next_index: usize,
+    on_waypoint_reached: Vec<WaypointReachedCallback>,
+    geofence: Option<Geofence>,
 }
 
 impl WaypointSystem{
-    pub fn new() -> Self {
+    pub fn new(geofence: Option<Geofence>) -> Self {
         Self {
             path: Vec::new(),
             current_waypoint: None,
             next_waypoint: None,
-            time_start_hold_ms: None,
+            hold_start: None,
             state: WaypointState::HOLD,
             offboard_active: false,
             last_position_ned: None,
             is_enabled: false,
+            setpoint_streamer: SetpointStreamer::new(SETPOINT_RATE_HZ, SETPOINT_MAX_SPEED_MPS),
+            current_index: None,
+            next_index: 0,
+            on_waypoint_reached: Vec::new(),
+            geofence,
         }
     }
 
+    /// Called exactly once per waypoint, when it is reached (COMPLETE)
+    pub fn on_waypoint_reached(&mut self, callback: WaypointReachedCallback) {
+        self.on_waypoint_reached.push(callback);
+    }
+
     pub fn add_waypoint(&mut self, waypoint: Waypoint) {
         self.path.push(waypoint);
     }
@@ -40,6 +69,7 @@ impl WaypointSystem{
 
     pub fn run_path(&mut self, path: Vec<Waypoint>) {
         self.path = path;
+        self.next_index = 0;
         self.is_enabled = true;
     }
 
@@ -51,7 +81,7 @@ impl AppSystemTrait for WaypointSystem{
         Ok(())
     }
     fn tick(&mut self, context: &crate::common::context::QuadAppContext) -> Result<(), anyhow::Error> {
-        self.tick_state_machine(context)?;
+        self.tick_state_machine(context, Instant::now())?;
         Ok(())
     }
 }
@@ -59,31 +89,59 @@ impl AppSystemTrait for WaypointSystem{
 // Tick Functions
 
 impl WaypointSystem{
-    fn tick_state_machine(&mut self, context: &crate::common::context::QuadAppContext) -> Result<(), anyhow::Error> {
+    fn tick_state_machine(&mut self, context: &crate::common::context::QuadAppContext, now: Instant) -> Result<(), anyhow::Error> {
         match self.state {
             WaypointState::HOLD => self.tick_hold(context)?,
-            WaypointState::COMMAND => self.tick_command(context)?,
-            WaypointState::TRANSIT => self.tick_transit(context)?,
-            WaypointState::COMPLETE => self.tick_complete(context)?,
+            WaypointState::COMMAND => self.tick_command(context, now)?,
+            WaypointState::TRANSIT => self.tick_transit(context, now)?,
+            WaypointState::COMPLETE => self.tick_complete(context, now)?,
         }
         Ok(())
     }
 
     fn tick_hold(&mut self, context: &crate::common::context::QuadAppContext) -> Result<(), anyhow::Error> {
+        if self.path.is_empty()
+            && let Some(path) = context.waypoint_paths.lock().unwrap().pop_front()
+        {
+            log::info!("WaypointSystem // HOLD - Starting queued path ({} waypoints)", path.len());
+            self.run_path(path);
+            context.state.write().unwrap().path_active = true;
+        }
         if !self.is_enabled {
-            log::warn!("WaypointSystem // HOLD - Not enabled");
+            log::debug!("WaypointSystem // HOLD - Not enabled");
             return Ok(());
         }
         // Check if there are any waypoints in the path
         if self.path.is_empty() {
-            self.is_enabled = false;
             log::warn!(
                 "WaypointSystem // HOLD - Path complete, disabling automatic processing"
             );
+            self.disable(context);
             return Ok(());
         }
-        // Pull the next waypoint from the path (index 0)
-        self.current_waypoint = Some(self.path.remove(0).clone());
+        // Pull the next waypoint from the path (index 0), skipping any outside the geofence
+        let (index, waypoint) = loop {
+            if self.path.is_empty() {
+                log::warn!(
+                    "WaypointSystem // HOLD - All remaining waypoints are outside the geofence, disabling automatic processing"
+                );
+                self.disable(context);
+                return Ok(());
+            }
+            let waypoint = self.path.remove(0);
+            let index = self.next_index;
+            self.next_index += 1;
+            if self.geofence.as_ref().is_none_or(|geofence| geofence.contains(&waypoint.ned)) {
+                break (index, waypoint);
+            }
+            log::warn!(
+                "WaypointSystem // HOLD - Skipping waypoint {} at {:?}, outside the geofence",
+                index,
+                waypoint.ned.to_array()
+            );
+        };
+        self.current_waypoint = Some(waypoint);
+        self.current_index = Some(index);
         if self.path.is_empty() {
             self.next_waypoint = None;
         } else {
@@ -99,26 +157,256 @@ impl WaypointSystem{
         Ok(())
     }
 
-    fn tick_command(&mut self, context: &crate::common::context::QuadAppContext) -> Result<(), anyhow::Error> {
-        log::info!("WaypointSystem // COMMAND - Starting offboard mode");
-        // Set initial setpoint to target position
-        let current_waypoint = self.current_waypoint.as_ref().unwrap().clone();
-        let target_ned = NED::new(
-            current_waypoint.ned.north,
-            current_waypoint.ned.east,
-            current_waypoint.ned.down,
+    fn tick_command(&mut self, context: &crate::common::context::QuadAppContext, now: Instant) -> Result<(), anyhow::Error> {
+        let Some(current_waypoint) = self.current_waypoint.clone() else {
+            self.state = WaypointState::HOLD;
+            return Ok(());
+        };
+        if !self.offboard_active {
+            log::info!("WaypointSystem // COMMAND - Starting offboard mode");
+            self.offboard_active = true;
+        }
+        log::info!(
+            "WaypointSystem // COMMAND - Heading to {:?} (radius {}m)",
+            current_waypoint.ned.to_array(),
+            current_waypoint.arrival_radius
         );
+        // Show the waypoint on the map, needs the EKF origin
+        if let Some(origin) = context.state.read().unwrap().lla_origin.clone() {
+            context.log_rerun.lock().unwrap().log_lla("quad/waypoint/lla", &current_waypoint.ned.to_lla(&origin));
+        }
+        // First setpoint goes out straight away, TRANSIT keeps streaming from there
+        let vehicle_ned = context.state.read().unwrap().ned_current.clone();
+        self.send_setpoint(context, now, &vehicle_ned, &current_waypoint);
 
+        // Transition to TRANSIT
+        self.state = WaypointState::TRANSIT;
         Ok(())
     }
 
-    fn tick_transit(&mut self, context: &crate::common::context::QuadAppContext) -> Result<(), anyhow::Error> {
-        log::info!("WaypointSystem // TRANSIT - Transiting to next waypoint");
+    fn tick_transit(&mut self, context: &crate::common::context::QuadAppContext, now: Instant) -> Result<(), anyhow::Error> {
+        let Some(current_waypoint) = self.current_waypoint.clone() else {
+            self.state = WaypointState::HOLD;
+            return Ok(());
+        };
+        let vehicle_ned = context.state.read().unwrap().ned_current.clone();
+        self.last_position_ned = Some(vehicle_ned.clone());
+        self.send_setpoint(context, now, &vehicle_ned, &current_waypoint);
+
+        let distance = vehicle_ned.distance(&current_waypoint.ned);
+        log::debug!("WaypointSystem // TRANSIT - {:.2}m to waypoint", distance);
+        if distance <= current_waypoint.arrival_radius {
+            log::info!("WaypointSystem // TRANSIT - Arrived, holding for {}s", current_waypoint.hold_time);
+            self.hold_start = Some(now);
+            // Transition to COMPLETE
+            self.state = WaypointState::COMPLETE;
+        }
         Ok(())
     }
 
-    fn tick_complete(&mut self, context: &crate::common::context::QuadAppContext) -> Result<(), anyhow::Error> {
-        log::info!("WaypointSystem // COMPLETE - Waypoint complete");
+    fn tick_complete(&mut self, context: &crate::common::context::QuadAppContext, now: Instant) -> Result<(), anyhow::Error> {
+        let Some(current_waypoint) = self.current_waypoint.clone() else {
+            self.state = WaypointState::HOLD;
+            return Ok(());
+        };
+        // Fires once, on the first COMPLETE tick for this waypoint
+        if let Some(index) = self.current_index.take() {
+            log::info!("WaypointSystem // COMPLETE - Waypoint {} reached", index);
+            let event = WaypointReachedEvent { index, waypoint: current_waypoint.clone() };
+            for callback in self.on_waypoint_reached.iter_mut() {
+                callback(context, &event);
+            }
+        }
+
+        // Keep streaming the waypoint while holding on it
+        let vehicle_ned = context.state.read().unwrap().ned_current.clone();
+        self.send_setpoint(context, now, &vehicle_ned, &current_waypoint);
+        let hold_time = Duration::from_secs_f32(current_waypoint.hold_time.max(0.0));
+        if self.hold_start.is_some_and(|hold_start| now.duration_since(hold_start) < hold_time) {
+            return Ok(());
+        }
+
+        log::info!("WaypointSystem // COMPLETE - Hold done");
+        self.hold_start = None;
+        // Back to HOLD to pull the next waypoint
+        self.state = WaypointState::HOLD;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn disable(&mut self, context: &crate::common::context::QuadAppContext) {
+        // Stop the vehicle where it is once we stop streaming setpoints
+        if self.offboard_active {
+            context.commands.lock().unwrap().push_back(QuadAppCommand::new(
+                QuadAppCommandType::Velocity(NED::new(0.0, 0.0, 0.0)),
+            ));
+        }
+        self.is_enabled = false;
+        self.offboard_active = false;
+        context.state.write().unwrap().path_active = false;
+        self.current_waypoint = None;
+        self.setpoint_streamer.reset();
+    }
+
+    fn send_setpoint(&mut self, context: &crate::common::context::QuadAppContext, now: Instant, vehicle_ned: &NED, waypoint: &Waypoint) {
+        if let Some(setpoint) = self.setpoint_streamer.tick(now, vehicle_ned, &waypoint.ned) {
+            context.commands.lock().unwrap().push_back(QuadAppCommand::new(
+                QuadAppCommandType::Position(setpoint, Some(waypoint.yaw_deg)),
+            ));
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::common::log_rerun::RerunSink;
+
+    fn test_context() -> QuadAppContext {
+        QuadAppContext::new("test".into(), RerunSink::Disabled)
+    }
+
+    fn set_vehicle(context: &QuadAppContext, ned: NED) {
+        context.state.write().unwrap().ned_current = ned;
+    }
+
+    fn waypoint(ned: NED) -> Waypoint {
+        Waypoint::new(ned, [255, 0, 0], 0.0, 0.5, 0.0, 0)
+    }
+
+    /// Ticks from HOLD until the waypoint is in TRANSIT
+    fn start_transit(system: &mut WaypointSystem, context: &QuadAppContext, waypoint: Waypoint, now: Instant) {
+        system.run_path(vec![waypoint]);
+        system.tick_state_machine(context, now).unwrap();
+        assert_eq!(system.state, WaypointState::COMMAND);
+        system.tick_state_machine(context, now).unwrap();
+        assert_eq!(system.state, WaypointState::TRANSIT);
+    }
+
+    #[test]
+    fn walks_hold_command_transit_complete_hold() {
+        let context = test_context();
+        let mut system = WaypointSystem::new(None);
+        let target = NED::new(3.0, 0.0, -2.0);
+        let t0 = Instant::now();
+        set_vehicle(&context, NED::new(0.0, 0.0, -2.0));
+        assert_eq!(system.state, WaypointState::HOLD);
+
+        start_transit(&mut system, &context, Waypoint::new(target.clone(), [0, 255, 0], 1.0, 0.5, 0.0, 0), t0);
+        // COMMAND sends the first setpoint straight away
+        assert!(matches!(
+            context.commands.lock().unwrap().pop_front().unwrap().cmd_type,
+            QuadAppCommandType::Position(_, Some(_))
+        ));
+
+        // Still far away
+        system.tick_state_machine(&context, t0 + Duration::from_millis(50)).unwrap();
+        assert_eq!(system.state, WaypointState::TRANSIT);
+
+        let arrived = t0 + Duration::from_millis(100);
+        set_vehicle(&context, target);
+        system.tick_state_machine(&context, arrived).unwrap();
+        assert_eq!(system.state, WaypointState::COMPLETE);
+        assert_eq!(system.hold_start, Some(arrived));
+
+        system.tick_state_machine(&context, arrived + Duration::from_secs(1)).unwrap();
+        assert_eq!(system.state, WaypointState::HOLD);
+        assert!(system.hold_start.is_none());
+
+        // Path is done, the system disables itself and brakes
+        context.commands.lock().unwrap().clear();
+        system.tick_state_machine(&context, arrived + Duration::from_secs(1)).unwrap();
+        assert_eq!(system.state, WaypointState::HOLD);
+        assert!(!system.is_enabled);
+        assert!(matches!(
+            context.commands.lock().unwrap().pop_front().unwrap().cmd_type,
+            QuadAppCommandType::Velocity(_)
+        ));
+    }
+
+    #[test]
+    fn arrival_radius_is_inclusive() {
+        let context = test_context();
+        let mut system = WaypointSystem::new(None);
+        let t0 = Instant::now();
+        set_vehicle(&context, NED::new(1.01, 0.0, -2.0));
+        start_transit(&mut system, &context, Waypoint::new(NED::new(0.0, 0.0, -2.0), [0, 0, 255], 0.0, 1.0, 0.0, 0), t0);
+
+        system.tick_state_machine(&context, t0).unwrap();
+        assert_eq!(system.state, WaypointState::TRANSIT);
+
+        set_vehicle(&context, NED::new(1.0, 0.0, -2.0));
+        system.tick_state_machine(&context, t0).unwrap();
+        assert_eq!(system.state, WaypointState::COMPLETE);
+    }
+
+    #[test]
+    fn holds_for_hold_time_before_pulling_the_next_waypoint() {
+        let context = test_context();
+        let mut system = WaypointSystem::new(None);
+        let target = NED::new(0.0, 0.0, -2.0);
+        let t0 = Instant::now();
+        set_vehicle(&context, target.clone());
+        start_transit(&mut system, &context, Waypoint::new(target, [255, 255, 255], 2.0, 0.5, 0.0, 0), t0);
+        system.tick_state_machine(&context, t0).unwrap();
+        assert_eq!(system.state, WaypointState::COMPLETE);
+
+        system.tick_state_machine(&context, t0 + Duration::from_millis(1999)).unwrap();
+        assert_eq!(system.state, WaypointState::COMPLETE);
+        system.tick_state_machine(&context, t0 + Duration::from_secs(2)).unwrap();
+        assert_eq!(system.state, WaypointState::HOLD);
+    }
+
+    #[test]
+    fn skips_waypoints_outside_the_geofence() {
+        let context = test_context();
+        let fence = Geofence::new(NED::new(-5.0, -5.0, -10.0), NED::new(5.0, 5.0, 0.0)).unwrap();
+        let mut system = WaypointSystem::new(Some(fence));
+        system.run_path(vec![waypoint(NED::new(5.01, 0.0, -2.0)), waypoint(NED::new(5.0, 0.0, -2.0))]);
+        system.tick_state_machine(&context, Instant::now()).unwrap();
+        assert_eq!(system.state, WaypointState::COMMAND);
+        assert_eq!(system.current_index, Some(1));
+    }
+
+    #[test]
+    fn disables_when_every_waypoint_is_outside_the_geofence() {
+        let context = test_context();
+        let fence = Geofence::new(NED::new(-5.0, -5.0, -10.0), NED::new(5.0, 5.0, 0.0)).unwrap();
+        let mut system = WaypointSystem::new(Some(fence));
+        system.run_path(vec![
+            waypoint(NED::new(6.0, 0.0, -2.0)),
+            waypoint(NED::new(0.0, -6.0, -2.0)),
+            waypoint(NED::new(0.0, 0.0, 1.0)),
+        ]);
+        system.tick_state_machine(&context, Instant::now()).unwrap();
+        assert_eq!(system.state, WaypointState::HOLD);
+        assert!(!system.is_enabled);
+        assert!(system.current_waypoint.is_none());
+        assert!(context.commands.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reached_event_fires_once_per_waypoint() {
+        let context = test_context();
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let mut system = WaypointSystem::new(None);
+        let events = fired.clone();
+        system.on_waypoint_reached(Box::new(move |_, event| events.lock().unwrap().push(event.index)));
+
+        let first = NED::new(0.0, 0.0, -2.0);
+        let second = NED::new(5.0, 0.0, -2.0);
+        set_vehicle(&context, first.clone());
+        system.run_path(vec![waypoint(first), waypoint(second.clone())]);
+        for _ in 0..10 {
+            system.tick(&context).unwrap();
+        }
+        assert_eq!(*fired.lock().unwrap(), vec![0]);
+
+        // Extra ticks after the path ran out must not fire again
+        set_vehicle(&context, second);
+        for _ in 0..20 {
+            system.tick(&context).unwrap();
+        }
+        assert_eq!(*fired.lock().unwrap(), vec![0, 1]);
+    }
+}
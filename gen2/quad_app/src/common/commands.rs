@@ -1,10 +1,14 @@
-use crate::link::mav_queues::MavQueues;
+use crate::common::state::NED;
 use mavlink::ardupilotmega::MavMessage;
 #[derive(Clone, Debug)]
 pub enum QuadAppCommandType{
     MavlinkRaw(MavMessage),
     QuadGuidedArm(),
     QuadTakeoff(),
+    /// Local NED position setpoint (m), yaw (deg) is left to the autopilot when None
+    Position(NED, Option<f32>),
+    /// Local NED velocity setpoint (m/s)
+    Velocity(NED),
 }
 
 
@@ -2,22 +2,26 @@ use std::collections::VecDeque;
 use std::sync::{Arc, Mutex, RwLock};
 
 use crate::common::commands::QuadAppCommand;
-use crate::common::log_rerun::LogRerun;
+use crate::common::log_rerun::{LogRerun, RerunSink};
 use crate::common::state::QuadAppState;
+use crate::common::waypoint::Waypoint;
 #[derive(Clone)]
 pub struct QuadAppContext {
     pub state: Arc<RwLock<QuadAppState>>,
     pub commands: Arc<Mutex<VecDeque<QuadAppCommand>>>,
+    /// Paths queued for WaypointSystem, flown one after another
+    pub waypoint_paths: Arc<Mutex<VecDeque<Vec<Waypoint>>>>,
     pub log_rerun: Arc<Mutex<LogRerun>>,
 
 }
 
 impl QuadAppContext {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, rerun_sink: RerunSink) -> Self {
         Self {
             state: Arc::new(RwLock::new(QuadAppState::new())),
             commands: Arc::new(Mutex::new(VecDeque::new())),
-            log_rerun: Arc::new(Mutex::new(LogRerun::new(name))),
+            waypoint_paths: Arc::new(Mutex::new(VecDeque::new())),
+            log_rerun: Arc::new(Mutex::new(LogRerun::new(name, rerun_sink))),
         }
     }
 }
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::state::NED;
+
+/// Axis-aligned box in the local NED frame, the edges count as inside
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "GeofenceBounds")]
+pub struct Geofence {
+    min: NED,
+    max: NED,
+}
+
+/// Unchecked form used for deserializing, goes through Geofence::new
+#[derive(Deserialize)]
+struct GeofenceBounds {
+    min: NED,
+    max: NED,
+}
+
+impl TryFrom<GeofenceBounds> for Geofence {
+    type Error = anyhow::Error;
+
+    fn try_from(bounds: GeofenceBounds) -> Result<Self, Self::Error> {
+        Geofence::new(bounds.min, bounds.max)
+    }
+}
+
+impl Geofence {
+    /// Fails when min is above max on any axis, such a box would reject every waypoint
+    pub fn new(min: NED, max: NED) -> Result<Self, anyhow::Error> {
+        for (axis, low, high) in [
+            ("north", min.north, max.north),
+            ("east", min.east, max.east),
+            ("down", min.down, max.down),
+        ] {
+            if !low.is_finite() || !high.is_finite() {
+                return Err(anyhow::anyhow!("Geofence // {} bounds must be finite", axis));
+            }
+            if low > high {
+                return Err(anyhow::anyhow!("Geofence // {} min {} is above max {}", axis, low, high));
+            }
+        }
+        Ok(Self { min, max })
+    }
+
+    pub fn contains(&self, ned: &NED) -> bool {
+        (self.min.north..=self.max.north).contains(&ned.north)
+            && (self.min.east..=self.max.east).contains(&ned.east)
+            && (self.min.down..=self.max.down).contains(&ned.down)
+    }
+}
+
+/// `--geofence MIN_N,MIN_E,MIN_D,MAX_N,MAX_E,MAX_D` in meters
+pub fn parse_geofence(value: &str) -> Result<Geofence, String> {
+    let values = value
+        .split(',')
+        .map(|v| v.trim().parse::<f32>().map_err(|e| format!("Invalid geofence value '{}': {}", v, e)))
+        .collect::<Result<Vec<f32>, String>>()?;
+    let [min_n, min_e, min_d, max_n, max_e, max_d] = values[..] else {
+        return Err(format!("Expected 6 comma separated values, got {}", values.len()));
+    };
+    Geofence::new(NED::new(min_n, min_e, min_d), NED::new(max_n, max_e, max_d)).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPS: f32 = 0.01;
+
+    fn fence() -> Geofence {
+        Geofence::new(NED::new(-10.0, -5.0, -20.0), NED::new(10.0, 5.0, 0.0)).unwrap()
+    }
+
+    #[test]
+    fn each_face_is_inclusive() {
+        let fence = fence();
+        assert!(fence.contains(&NED::new(0.0, 0.0, -10.0)));
+        // Point on each face, with the outward direction
+        let faces = [
+            (NED::new(-10.0, 0.0, -10.0), [-1.0, 0.0, 0.0]),
+            (NED::new(10.0, 0.0, -10.0), [1.0, 0.0, 0.0]),
+            (NED::new(0.0, -5.0, -10.0), [0.0, -1.0, 0.0]),
+            (NED::new(0.0, 5.0, -10.0), [0.0, 1.0, 0.0]),
+            (NED::new(0.0, 0.0, -20.0), [0.0, 0.0, -1.0]),
+            (NED::new(0.0, 0.0, 0.0), [0.0, 0.0, 1.0]),
+        ];
+        for (face, [n, e, d]) in faces {
+            let step = |distance: f32| NED::new(face.north + n * distance, face.east + e * distance, face.down + d * distance);
+            assert!(fence.contains(&face), "{:?} on the face", face.to_array());
+            assert!(fence.contains(&step(-EPS)), "{:?} just inside", step(-EPS).to_array());
+            assert!(!fence.contains(&step(EPS)), "{:?} just outside", step(EPS).to_array());
+        }
+    }
+
+    #[test]
+    fn new_rejects_min_above_max() {
+        assert!(Geofence::new(NED::new(1.0, 0.0, 0.0), NED::new(0.0, 1.0, 1.0)).is_err());
+        assert!(Geofence::new(NED::new(0.0, 1.0, 0.0), NED::new(1.0, 0.0, 1.0)).is_err());
+        assert!(Geofence::new(NED::new(0.0, 0.0, 1.0), NED::new(1.0, 1.0, 0.0)).is_err());
+        assert!(Geofence::new(NED::new(0.0, 0.0, f32::NAN), NED::new(1.0, 1.0, 1.0)).is_err());
+        // A flat box is allowed
+        assert!(Geofence::new(NED::new(0.0, 0.0, -2.0), NED::new(1.0, 1.0, -2.0)).is_ok());
+    }
+
+    #[test]
+    fn deserialize_validates() {
+        let ok = r#"{"min":{"north":-1.0,"east":-1.0,"down":-5.0},"max":{"north":1.0,"east":1.0,"down":0.0}}"#;
+        assert!(serde_json::from_str::<Geofence>(ok).is_ok());
+        let inverted = r#"{"min":{"north":1.0,"east":-1.0,"down":-5.0},"max":{"north":-1.0,"east":1.0,"down":0.0}}"#;
+        assert!(serde_json::from_str::<Geofence>(inverted).is_err());
+    }
+
+    #[test]
+    fn parses_cli_value() {
+        let fence = parse_geofence("-10,-5,-20,10,5,0").unwrap();
+        assert!(fence.contains(&NED::new(10.0, 5.0, 0.0)));
+        assert!(parse_geofence("-10,-5,-20,10,5").is_err());
+        assert!(parse_geofence("10,-5,-20,-10,5,0").is_err());
+        assert!(parse_geofence("a,-5,-20,10,5,0").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HealthStatus {
+    #[default]
+    AwaitingLock,
+    Healthy,
+    Flapping,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthDebounceConfig {
+    /// How long the raw health must stay good before reporting Healthy
+    pub dwell_ms: u64,
+    pub flap_window_ms: u64,
+    /// Transitions within flap_window_ms that count as flapping
+    pub flap_transitions: usize,
+}
+
+impl Default for HealthDebounceConfig {
+    fn default() -> Self {
+        Self {
+            dwell_ms: 3000,
+            flap_window_ms: 10000,
+            flap_transitions: 4,
+        }
+    }
+}
+
+/// Hysteresis over a raw healthy/unhealthy signal (e.g. EkfStatus::is_healthy)
+pub struct HealthDebounce {
+    config: HealthDebounceConfig,
+    transitions: VecDeque<Instant>,
+    raw_healthy: bool,
+    healthy_since: Option<Instant>,
+}
+
+impl HealthDebounce {
+    pub fn new(config: HealthDebounceConfig) -> Self {
+        Self {
+            config,
+            transitions: VecDeque::new(),
+            raw_healthy: false,
+            healthy_since: None,
+        }
+    }
+
+    pub fn update(&mut self, healthy: bool, now: Instant) -> HealthStatus {
+        if healthy != self.raw_healthy {
+            self.transitions.push_back(now);
+            self.raw_healthy = healthy;
+            self.healthy_since = if healthy { Some(now) } else { None };
+        }
+
+        let flap_window = Duration::from_millis(self.config.flap_window_ms);
+        while let Some(transition) = self.transitions.front() {
+            if now.duration_since(*transition) > flap_window {
+                self.transitions.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let dwell = Duration::from_millis(self.config.dwell_ms);
+        if self.healthy_since.is_some_and(|since| now.duration_since(since) >= dwell) {
+            HealthStatus::Healthy
+        } else if self.transitions.len() >= self.config.flap_transitions {
+            HealthStatus::Flapping
+        } else {
+            HealthStatus::AwaitingLock
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debounce() -> HealthDebounce {
+        HealthDebounce::new(HealthDebounceConfig {
+            dwell_ms: 3000,
+            flap_window_ms: 10000,
+            flap_transitions: 4,
+        })
+    }
+
+    fn ms(t0: Instant, ms: u64) -> Instant {
+        t0 + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn healthy_only_after_dwell() {
+        let mut debounce = debounce();
+        let t0 = Instant::now();
+        assert_eq!(debounce.update(false, t0), HealthStatus::AwaitingLock);
+        assert_eq!(debounce.update(true, ms(t0, 100)), HealthStatus::AwaitingLock);
+        assert_eq!(debounce.update(true, ms(t0, 3099)), HealthStatus::AwaitingLock);
+        assert_eq!(debounce.update(true, ms(t0, 3100)), HealthStatus::Healthy);
+        // Dropping out is reported straight away
+        assert_eq!(debounce.update(false, ms(t0, 3200)), HealthStatus::AwaitingLock);
+    }
+
+    #[test]
+    fn flapping_at_flap_transitions() {
+        let mut debounce = debounce();
+        let t0 = Instant::now();
+        // true, false, true: 3 transitions
+        assert_eq!(debounce.update(true, ms(t0, 0)), HealthStatus::AwaitingLock);
+        assert_eq!(debounce.update(false, ms(t0, 500)), HealthStatus::AwaitingLock);
+        assert_eq!(debounce.update(true, ms(t0, 1000)), HealthStatus::AwaitingLock);
+        // 4th transition
+        assert_eq!(debounce.update(false, ms(t0, 1500)), HealthStatus::Flapping);
+        assert_eq!(debounce.update(true, ms(t0, 2000)), HealthStatus::Flapping);
+    }
+
+    #[test]
+    fn flap_window_ages_out() {
+        let mut debounce = debounce();
+        let t0 = Instant::now();
+        for (i, healthy) in [true, false, true, false].into_iter().enumerate() {
+            debounce.update(healthy, ms(t0, i as u64 * 500));
+        }
+        assert_eq!(debounce.update(false, ms(t0, 2000)), HealthStatus::Flapping);
+        // All four transitions are older than flap_window_ms
+        assert_eq!(debounce.update(false, ms(t0, 11600)), HealthStatus::AwaitingLock);
+        // Stable again: Healthy after the dwell even though it flapped earlier
+        assert_eq!(debounce.update(true, ms(t0, 12000)), HealthStatus::AwaitingLock);
+        assert_eq!(debounce.update(true, ms(t0, 15000)), HealthStatus::Healthy);
+    }
+}
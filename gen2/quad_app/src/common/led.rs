@@ -1,18 +1,37 @@
-#[derive(Default, Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LedAnimation {
+    #[default]
+    Solid,
+    /// Blink at this rate, 1-255 Hz
+    Blink { rate_hz: u8 },
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LED{
     pub rgb: [u8; 3],
     pub brightness: f32,
     pub is_on: bool,
+    pub animation: LedAnimation,
 }
 
 
 impl LED{
-    pub fn new(rgb: [u8; 3], brightness: f32, is_on: bool) -> Self {
-        Self { rgb, brightness, is_on }
+    pub fn new(rgb: [u8; 3], brightness: f32, is_on: bool, animation: LedAnimation) -> Self {
+        Self { rgb, brightness, is_on, animation }
+    }
+
+    /// Colour actually shown, scaled by brightness (0-1) and black when off
+    pub fn output_rgb(&self) -> [u8; 3] {
+        if !self.is_on {
+            return [0, 0, 0];
+        }
+        let brightness = self.brightness.clamp(0.0, 1.0);
+        self.rgb.map(|channel| (channel as f32 * brightness).round() as u8)
     }
 
     pub fn to_rerun_color(&self) -> rerun::components::Color {
         rerun::components::Color::new([self.rgb[0], self.rgb[1], self.rgb[2]])
     }
 }
-
@@ -1,44 +1,113 @@
-use crate::common::state::{LLA, NED};
+use std::time::{Duration, Instant};
 
+use clap::ValueEnum;
+
+use crate::common::state::LLA;
+
+const WARN_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RerunSink {
+    /// Spawn (or connect to) a local rerun viewer
+    #[default]
+    Spawn,
+    /// No-op, for headless rigs
+    Disabled,
+}
+
+/// Rerun logging never fails the caller: errors are logged as a warning,
+/// at most once per WARN_INTERVAL, and otherwise ignored.
 pub struct LogRerun {
     pub name: String,
-    pub rec: rerun::RecordingStream,
+    pub rec: Option<rerun::RecordingStream>,
+    last_warn: Option<Instant>,
+    suppressed_warnings: u32,
 }
 
 impl LogRerun {
-    pub fn new(name: String) -> Self {
-        let rec = rerun::RecordingStreamBuilder::new(name.clone())
-            .spawn()
-            .unwrap();
-        Self { name, rec: rec }
+    pub fn new(name: String, sink: RerunSink) -> Self {
+        let rec = match sink {
+            RerunSink::Spawn => match rerun::RecordingStreamBuilder::new(name.clone()).spawn() {
+                Ok(rec) => Some(rec),
+                Err(e) => {
+                    log::warn!("LogRerun // Could not spawn rerun viewer, rerun logging disabled: {}", e);
+                    None
+                }
+            },
+            RerunSink::Disabled => None,
+        };
+        Self {
+            name,
+            rec,
+            last_warn: None,
+            suppressed_warnings: 0,
+        }
     }
 
-    pub fn log_status_text(&self, topic: &str, status_text: &str) -> Result<(), anyhow::Error> {
+    pub fn log_status_text(&mut self, topic: &str, status_text: &str) {
         log::info!("LogRerun // MAVLINK: {}", status_text);
-        self.rec.log(
-            topic.to_string(),
+        self.log_entity(
+            topic,
             &rerun::TextLog::new(status_text.to_string()).with_level(rerun::TextLogLevel::INFO),
-        )?;
-        Ok(())
+        );
+    }
+
+    pub fn log_event(&mut self, topic: &str, event: &str) {
+        self.log_entity(
+            topic,
+            &rerun::TextLog::new(event.to_string()).with_level(rerun::TextLogLevel::INFO),
+        );
     }
 
-    pub fn log_lla(&self, topic: &str, lla: &LLA) -> Result<(), anyhow::Error> {
-        self.rec.log(
-            topic.to_string(),
+    pub fn log_json(&mut self, topic: &str, json: &str) {
+        self.log_entity(topic, &rerun::TextDocument::new(json.to_string()));
+    }
+
+    pub fn log_lla(&mut self, topic: &str, lla: &LLA) {
+        self.log_entity(
+            topic,
             &rerun::GeoPoints::from_lat_lon(&[(lla.latitude as f64, lla.longitude as f64)])
                 .with_radii([rerun::Radius::new_ui_points(5.0)])
                 .with_colors([rerun::Color::from_rgb(255, 0, 0)]),
-        )?;
-        Ok(())
+        );
+    }
+
+    pub fn log_scalar(&mut self, topic: &str, value: f32) {
+        self.log_entity(topic, &rerun::Scalars::single(value as f64));
     }
 
-    pub fn log_ned(&self, topic: &str, ned: &NED) -> Result<(), anyhow::Error> {
-        self.rec.log(
-            topic.to_string(),
-            &rerun::Points3D::new(&[[ned.north as f64, ned.east as f64, -ned.down as f64]])
+    /// `xyz` is expected with z up, see TelemetryOutput::position_z_up
+    pub fn log_position(&mut self, topic: &str, xyz: [f32; 3]) {
+        self.log_entity(
+            topic,
+            &rerun::Points3D::new(&[xyz.map(|v| v as f64)])
                 .with_radii([rerun::Radius::new_ui_points(5.0)])
                 .with_colors([rerun::Color::from_rgb(255, 0, 0)]),
-        )?;
-        Ok(())
+        );
+    }
+
+    fn log_entity(&mut self, topic: &str, entity: &impl rerun::AsComponents) {
+        let Some(rec) = &self.rec else {
+            return;
+        };
+        if let Err(e) = rec.log(topic.to_string(), entity) {
+            self.warn_throttled(topic, &e.to_string());
+        }
+    }
+
+    fn warn_throttled(&mut self, topic: &str, error: &str) {
+        let now = Instant::now();
+        if self.last_warn.is_some_and(|last_warn| now.duration_since(last_warn) < WARN_INTERVAL) {
+            self.suppressed_warnings += 1;
+            return;
+        }
+        log::warn!(
+            "LogRerun // Failed to log {} ({} similar warnings suppressed): {}",
+            topic,
+            self.suppressed_warnings,
+            error
+        );
+        self.last_warn = Some(now);
+        self.suppressed_warnings = 0;
     }
 }
@@ -1,4 +1,5 @@
 use mavlink::ardupilotmega::MavMessage;
+use serde::{Deserialize, Serialize};
 
 pub fn mavlink_msg_type_str(msg: &MavMessage) -> String {
     let message_type = format!("{:?}", msg);
@@ -18,7 +19,7 @@ pub fn mavlink_msg_type_str(msg: &MavMessage) -> String {
 }
 
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EkfStatus {
     pub attitude: bool,
     pub vel_horiz: bool,
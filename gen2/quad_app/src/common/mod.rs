@@ -4,4 +4,8 @@ pub mod mavlink_helpers;
 pub mod context;
 pub mod log_rerun;
 pub mod led;
-pub mod waypoint;
\ No newline at end of file
+pub mod waypoint;
+pub mod setpoint_streamer;
+pub mod health;
+pub mod telemetry_output;
+pub mod geofence;
\ No newline at end of file
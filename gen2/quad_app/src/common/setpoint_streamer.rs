@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use crate::common::state::NED;
+
+/// Rate-limited, interpolated position setpoints.
+///
+/// Emits at most one setpoint per `1 / rate_hz`, and only when ticked, so the caller has to
+/// tick at least at `rate_hz` to get that rate (QuadApp ticks at AppConfig::tick_hz).
+/// Each emitted setpoint moves from the previous one towards the target by at most
+/// `max_speed_mps` times the time actually elapsed, so a late tick produces a larger
+/// step rather than a slower vehicle.
+pub struct SetpointStreamer {
+    rate_hz: f32,
+    max_speed_mps: f32,
+    last_emit: Option<Instant>,
+    setpoint: Option<NED>,
+}
+
+impl SetpointStreamer {
+    pub fn new(rate_hz: f32, max_speed_mps: f32) -> Self {
+        Self {
+            rate_hz,
+            max_speed_mps,
+            last_emit: None,
+            setpoint: None,
+        }
+    }
+
+    pub fn period(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate_hz as f64)
+    }
+
+    /// Forget the last setpoint so the next one starts again from the vehicle position
+    pub fn reset(&mut self) {
+        self.last_emit = None;
+        self.setpoint = None;
+    }
+
+    /// Returns the next setpoint when a period has elapsed since the last one, otherwise None
+    pub fn tick(&mut self, now: Instant, vehicle: &NED, target: &NED) -> Option<NED> {
+        let (from, elapsed) = match (&self.setpoint, self.last_emit) {
+            (Some(setpoint), Some(last_emit)) => {
+                let elapsed = now.saturating_duration_since(last_emit);
+                if elapsed < self.period() {
+                    return None;
+                }
+                (setpoint.clone(), elapsed)
+            }
+            _ => (vehicle.clone(), self.period()),
+        };
+
+        let remaining = from.distance(target);
+        let max_step = self.max_speed_mps * elapsed.as_secs_f32();
+        let next = if remaining <= max_step || remaining == 0.0 {
+            target.clone()
+        } else {
+            let ratio = max_step / remaining;
+            NED::new(
+                from.north + (target.north - from.north) * ratio,
+                from.east + (target.east - from.east) * ratio,
+                from.down + (target.down - from.down) * ratio,
+            )
+        };
+
+        self.last_emit = Some(now);
+        self.setpoint = Some(next.clone());
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ned(actual: Option<NED>, expected: [f32; 3]) {
+        let actual = actual.expect("expected a setpoint").to_array();
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn emits_once_per_period() {
+        // 20Hz, 2m/s -> 0.1m per period
+        let mut streamer = SetpointStreamer::new(20.0, 2.0);
+        let vehicle = NED::default();
+        let target = NED::new(10.0, 0.0, 0.0);
+        let t0 = Instant::now();
+
+        assert_ned(streamer.tick(t0, &vehicle, &target), [0.1, 0.0, 0.0]);
+        assert!(streamer.tick(t0 + Duration::from_millis(10), &vehicle, &target).is_none());
+        assert!(streamer.tick(t0 + Duration::from_millis(49), &vehicle, &target).is_none());
+        assert_ned(streamer.tick(t0 + Duration::from_millis(50), &vehicle, &target), [0.2, 0.0, 0.0]);
+
+        let emitted = (1..=20)
+            .filter_map(|i| streamer.tick(t0 + Duration::from_millis(50 + i * 10), &vehicle, &target))
+            .count();
+        assert_eq!(emitted, 4);
+    }
+
+    #[test]
+    fn late_tick_takes_a_larger_step() {
+        let mut streamer = SetpointStreamer::new(20.0, 2.0);
+        let vehicle = NED::default();
+        let target = NED::new(0.0, 10.0, 0.0);
+        let t0 = Instant::now();
+        streamer.tick(t0, &vehicle, &target);
+        // 150ms late at 2m/s is 0.3m on top of the first 0.1m
+        assert_ned(streamer.tick(t0 + Duration::from_millis(150), &vehicle, &target), [0.0, 0.4, 0.0]);
+    }
+
+    #[test]
+    fn interpolates_along_the_line_and_stops_at_target() {
+        let mut streamer = SetpointStreamer::new(10.0, 5.0);
+        let vehicle = NED::new(0.0, 0.0, -2.0);
+        let target = NED::new(3.0, 4.0, -2.0);
+        let t0 = Instant::now();
+        // 0.5m per period along a 5m line
+        assert_ned(streamer.tick(t0, &vehicle, &target), [0.3, 0.4, -2.0]);
+        let last = (1..=20)
+            .filter_map(|i| streamer.tick(t0 + Duration::from_millis(i * 100), &vehicle, &target))
+            .last();
+        assert_ned(last, [3.0, 4.0, -2.0]);
+    }
+
+    #[test]
+    fn reset_restarts_from_vehicle() {
+        let mut streamer = SetpointStreamer::new(20.0, 2.0);
+        let target = NED::new(10.0, 0.0, 0.0);
+        let t0 = Instant::now();
+        streamer.tick(t0, &NED::default(), &target);
+        streamer.reset();
+        assert_ned(streamer.tick(t0, &NED::new(5.0, 0.0, 0.0), &target), [5.1, 0.0, 0.0]);
+    }
+}
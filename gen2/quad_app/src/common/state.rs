@@ -1,12 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+use crate::common::health::HealthStatus;
 use crate::common::led::LED;
 use crate::common::mavlink_helpers::EkfStatus;
-#[derive(Default, Debug, Clone)]
+use crate::link::mav_mode::ArduMode;
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct LLA {
     pub latitude: f32,
     pub longitude: f32,
     pub altitude: f32,
 }
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct NED {
     pub north: f32,
     pub east: f32,
@@ -17,12 +21,29 @@ impl NED {
     pub fn new(north: f32, east: f32, down: f32) -> Self {
         Self { north, east, down }
     }
+    pub fn to_array(&self) -> [f32; 3] {
+        [self.north, self.east, self.down]
+    }
+
+    /// [east, north, up]
+    pub fn to_enu(&self) -> [f32; 3] {
+        [self.east, self.north, -self.down]
+    }
+
     pub fn distance(&self, other: &NED) -> f32 {
         ((self.north - other.north).powi(2)
             + (self.east - other.east).powi(2)
             + (self.down - other.down).powi(2))
         .sqrt()
     }
+
+    /// Inverse of LLA::to_ned, same accuracy limits
+    pub fn to_lla(&self, origin: &LLA) -> LLA {
+        let (north_m_per_rad, east_m_per_rad) = meters_per_radian(origin.latitude as f64);
+        let latitude = origin.latitude as f64 + (self.north as f64 / north_m_per_rad).to_degrees();
+        let longitude = origin.longitude as f64 + (self.east as f64 / east_m_per_rad).to_degrees();
+        LLA::new(latitude as f32, longitude as f32, origin.altitude - self.down)
+    }
 }
 
 impl LLA {
@@ -33,19 +54,63 @@ impl LLA {
             altitude,
         }
     }
+
+    /// Local tangent plane (equirectangular) offset from `origin`, scaled by the WGS84 radii
+    /// at the origin latitude. Over a few hundred meters the projection is off by well under 1cm
+    /// horizontally, but `down` ignores earth curvature (about 1cm at 350m).
+    /// LLA -> NED -> LLA round trips within 1cm. NED -> LLA -> NED does not, the f32
+    /// latitude/longitude in LLA only resolve a few tenths of a meter.
+    pub fn to_ned(&self, origin: &LLA) -> NED {
+        let (north_m_per_rad, east_m_per_rad) = meters_per_radian(origin.latitude as f64);
+        let d_lat = (self.latitude as f64 - origin.latitude as f64).to_radians();
+        let d_lon = (self.longitude as f64 - origin.longitude as f64).to_radians();
+        NED::new(
+            (d_lat * north_m_per_rad) as f32,
+            (d_lon * east_m_per_rad) as f32,
+            origin.altitude - self.altitude,
+        )
+    }
+}
+
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_E2: f64 = 6.694_379_990_14e-3;
+
+/// Meters per radian of (latitude, longitude) at `latitude_deg`
+fn meters_per_radian(latitude_deg: f64) -> (f64, f64) {
+    let latitude = latitude_deg.to_radians();
+    let w = 1.0 - WGS84_E2 * latitude.sin().powi(2);
+    let meridional = WGS84_A * (1.0 - WGS84_E2) / w.powf(1.5);
+    let prime_vertical = WGS84_A / w.sqrt();
+    (meridional, prime_vertical * latitude.cos())
 }
 
 const MIN_DISTANCE_TO_RECORD_NED: f32 = 0.01;
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct QuadAppState {
     pub status_message: Option<String>,
 
     pub lla_current: LLA,
+    /// EKF origin from GPS_GLOBAL_ORIGIN, ned_current is relative to it. None until received.
+    pub lla_origin: Option<LLA>,
     pub ned_current: NED,
+    /// Published incrementally by SysStatePublish, so left out of to_json
+    #[serde(skip)]
     pub ned_history: Vec<NED>,
+    /// Bumped by clear_ned_history, so consumers of ned_history can tell a restart from growth
+    #[serde(skip)]
+    pub ned_history_generation: u64,
 
     pub ekf_status: EkfStatus,
+    /// Debounced EKF health, use this to gate missions
+    pub health_status: HealthStatus,
+
+    /// From the autopilot HEARTBEAT, None until the first one arrives
+    pub mode: Option<ArduMode>,
+    pub armed: bool,
+    /// True while WaypointSystem is flying a path
+    #[serde(skip)]
+    pub path_active: bool,
 
     pub led_state: LED,
 }
@@ -55,9 +120,15 @@ impl QuadAppState {
         Self {
             status_message: None,
             lla_current: LLA::default(),
+            lla_origin: None,
             ned_current: NED::default(),
             ned_history: Vec::new(),
+            ned_history_generation: 0,
             ekf_status: EkfStatus::default(),
+            health_status: HealthStatus::default(),
+            mode: None,
+            armed: false,
+            path_active: false,
             led_state: LED::default(),
         }
     }
@@ -66,15 +137,121 @@ impl QuadAppState {
         self.ned_current = ned;
 
         // Only save if the NED is at least 0.01m away from the last entry
-        if self.ned_history.len() > 0 {
-            let last_ned = self.ned_history.last().unwrap();
-            if last_ned.distance(&self.ned_current) > MIN_DISTANCE_TO_RECORD_NED {
-                self.ned_history.push(self.ned_current.clone());
-            }
+        let is_new_point = self
+            .ned_history
+            .last()
+            .is_none_or(|last_ned| last_ned.distance(&self.ned_current) > MIN_DISTANCE_TO_RECORD_NED);
+        if is_new_point {
+            self.ned_history.push(self.ned_current.clone());
         }
     }
 
+    pub fn clear_ned_history(&mut self) {
+        self.ned_history.clear();
+        self.ned_history_generation += 1;
+    }
+
     pub fn record_lla(&mut self, lla: LLA) {
         self.lla_current = lla;
     }
+
+    /// Whole state as one JSON object, field names are stable for schema generation
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_round_trips() {
+        let mut state = QuadAppState::new();
+        state.status_message = Some("EKF ok".to_string());
+        state.record_lla(LLA::new(47.397742, 8.545594, 488.0));
+        state.record_ned(NED::new(1.5, -2.0, -3.25));
+        state.health_status = HealthStatus::Healthy;
+        state.mode = Some(ArduMode::Guided);
+        state.armed = true;
+        state.led_state.rgb = [255, 0, 128];
+
+        let json = state.to_json().unwrap();
+        let parsed: QuadAppState = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.to_json().unwrap(), json);
+        assert_eq!(parsed.ned_current.to_array(), [1.5, -2.0, -3.25]);
+        assert_eq!(parsed.mode, Some(ArduMode::Guided));
+    }
+
+    #[test]
+    fn to_json_field_names_are_stable() {
+        let value: serde_json::Value = serde_json::from_str(&QuadAppState::new().to_json().unwrap()).unwrap();
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "armed",
+                "ekf_status",
+                "health_status",
+                "led_state",
+                "lla_current",
+                "lla_origin",
+                "mode",
+                "ned_current",
+                "status_message"
+            ]
+        );
+    }
+
+    fn origin() -> LLA {
+        LLA::new(47.397742, 8.545594, 488.0)
+    }
+
+    /// Horizontal error between two LLAs in meters
+    fn lla_error_m(a: &LLA, b: &LLA) -> f64 {
+        let (north_m_per_rad, east_m_per_rad) = meters_per_radian(a.latitude as f64);
+        let north = (a.latitude as f64 - b.latitude as f64).to_radians() * north_m_per_rad;
+        let east = (a.longitude as f64 - b.longitude as f64).to_radians() * east_m_per_rad;
+        (north * north + east * east).sqrt()
+    }
+
+    /// Published WGS84 lengths of one degree of latitude / longitude
+    #[test]
+    fn meters_per_degree_match_wgs84() {
+        let (north, east) = meters_per_radian(0.0);
+        assert!((north.to_radians() - 110_574.3).abs() < 0.1);
+        assert!((east.to_radians() - 111_319.5).abs() < 0.1);
+        let (north, east) = meters_per_radian(45.0);
+        assert!((north.to_radians() - 111_131.8).abs() < 0.1);
+        assert!((east.to_radians() - 78_846.8).abs() < 0.1);
+    }
+
+    #[test]
+    fn lla_ned_lla_round_trips_within_1cm() {
+        let origin = origin();
+        for (north, east, down) in [
+            (0.0, 0.0, 0.0),
+            (300.0, 0.0, -10.0),
+            (0.0, -300.0, -50.0),
+            (-212.0, 212.0, 5.0),
+            (123.456, -78.9, -2.5),
+        ] {
+            let lla = NED::new(north, east, down).to_lla(&origin);
+            let round_trip = lla.to_ned(&origin).to_lla(&origin);
+            let error = lla_error_m(&lla, &round_trip);
+            assert!(error < 0.01, "{:?} came back {:?}, {}m off", lla, round_trip, error);
+            assert!((lla.altitude - round_trip.altitude).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn to_ned_matches_the_offset() {
+        let origin = origin();
+        let ned = NED::new(250.0, -150.0, -20.0).to_lla(&origin).to_ned(&origin);
+        // Bounded by f32 latitude / longitude resolution, not by the projection
+        assert!((ned.north - 250.0).abs() < 0.5, "{:?}", ned.to_array());
+        assert!((ned.east + 150.0).abs() < 0.5, "{:?}", ned.to_array());
+        assert!((ned.down + 20.0).abs() < 0.01, "{:?}", ned.to_array());
+    }
 }
@@ -0,0 +1,162 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::common::state::NED;
+
+const FEET_PER_METER: f32 = 3.28084;
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFrame {
+    /// x north, y east, z down
+    #[default]
+    Ned,
+    /// x east, y north, z up
+    Enu,
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputUnits {
+    /// Meters
+    #[default]
+    Metric,
+    /// Feet
+    Imperial,
+}
+
+#[derive(ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputAngles {
+    /// Degrees
+    #[default]
+    Degrees,
+    /// Radians
+    Radians,
+}
+
+/// Frame / unit transform applied by the telemetry tasks before logging.
+/// QuadAppState always stays in SI / NED / degrees. Topics get a suffix when not in
+/// the default units, so a viewer never mixes e.g. meters and feet on one entity.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct TelemetryOutput {
+    pub frame: OutputFrame,
+    pub units: OutputUnits,
+    #[serde(default)]
+    pub angles: OutputAngles,
+}
+
+impl TelemetryOutput {
+    pub fn new(frame: OutputFrame, units: OutputUnits, angles: OutputAngles) -> Self {
+        Self { frame, units, angles }
+    }
+
+    pub fn length(&self, meters: f32) -> f32 {
+        match self.units {
+            OutputUnits::Metric => meters,
+            OutputUnits::Imperial => meters * FEET_PER_METER,
+        }
+    }
+
+    pub fn angle(&self, degrees: f32) -> f32 {
+        match self.angles {
+            OutputAngles::Degrees => degrees,
+            OutputAngles::Radians => degrees.to_radians(),
+        }
+    }
+
+    /// Position as [x, y, z] in the output frame and units
+    pub fn position(&self, ned: &NED) -> [f32; 3] {
+        let xyz = match self.frame {
+            OutputFrame::Ned => ned.to_array(),
+            OutputFrame::Enu => ned.to_enu(),
+        };
+        xyz.map(|v| self.length(v))
+    }
+
+    /// Same position with z pointing up, for 3D viewers
+    pub fn position_z_up(&self, ned: &NED) -> [f32; 3] {
+        let [x, y, z] = self.position(ned);
+        match self.frame {
+            OutputFrame::Ned => [x, y, -z],
+            OutputFrame::Enu => [x, y, z],
+        }
+    }
+
+    /// e.g. `mavlink/position/enu_ft`
+    pub fn position_topic(&self, base: &str) -> String {
+        let frame = match self.frame {
+            OutputFrame::Ned => "ned",
+            OutputFrame::Enu => "enu",
+        };
+        format!("{}/{}{}", base, frame, self.length_suffix())
+    }
+
+    /// e.g. `mavlink/position/altitude_ft`
+    pub fn length_topic(&self, topic: &str) -> String {
+        format!("{}{}", topic, self.length_suffix())
+    }
+
+    /// e.g. `mavlink/position/latitude_rad`
+    pub fn angle_topic(&self, topic: &str) -> String {
+        match self.angles {
+            OutputAngles::Degrees => topic.to_string(),
+            OutputAngles::Radians => format!("{}_rad", topic),
+        }
+    }
+
+    fn length_suffix(&self) -> &'static str {
+        match self.units {
+            OutputUnits::Metric => "",
+            OutputUnits::Imperial => "_ft",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn ned_is_passed_through() {
+        let output = TelemetryOutput::default();
+        let ned = NED::new(1.0, 2.0, -3.0);
+        assert_eq!(output.position(&ned), [1.0, 2.0, -3.0]);
+        assert_eq!(output.position_z_up(&ned), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn enu_swaps_north_east_and_flips_down() {
+        let output = TelemetryOutput::new(OutputFrame::Enu, OutputUnits::Metric, OutputAngles::Degrees);
+        let ned = NED::new(1.0, 2.0, -3.0);
+        assert_eq!(output.position(&ned), [2.0, 1.0, 3.0]);
+        assert_eq!(output.position_z_up(&ned), [2.0, 1.0, 3.0]);
+    }
+
+    #[test]
+    fn imperial_scales_meters_to_feet() {
+        let output = TelemetryOutput::new(OutputFrame::Ned, OutputUnits::Imperial, OutputAngles::Degrees);
+        assert!((output.length(1.0) - 3.28084).abs() < 1e-5);
+        assert!((output.length(100.0) - 328.084).abs() < 1e-3);
+        assert_close(output.position(&NED::new(1.0, -2.0, 0.5)), [3.28084, -6.56168, 1.64042]);
+    }
+
+    #[test]
+    fn radians_convert_degrees() {
+        let output = TelemetryOutput::new(OutputFrame::Ned, OutputUnits::Metric, OutputAngles::Radians);
+        assert!((output.angle(180.0) - std::f32::consts::PI).abs() < 1e-6);
+        assert_eq!(TelemetryOutput::default().angle(47.5), 47.5);
+    }
+
+    #[test]
+    fn topics_name_frame_and_units() {
+        assert_eq!(TelemetryOutput::default().position_topic("mavlink/position"), "mavlink/position/ned");
+        let output = TelemetryOutput::new(OutputFrame::Enu, OutputUnits::Imperial, OutputAngles::Radians);
+        assert_eq!(output.position_topic("mavlink/position"), "mavlink/position/enu_ft");
+        assert_eq!(output.length_topic("mavlink/position/altitude"), "mavlink/position/altitude_ft");
+        assert_eq!(output.angle_topic("mavlink/position/latitude"), "mavlink/position/latitude_rad");
+    }
+}
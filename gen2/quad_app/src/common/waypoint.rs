@@ -5,12 +5,14 @@ pub struct Waypoint{
     pub ned: NED,
     pub color: [u8; 3],
     pub hold_time: f32,
+    /// Distance in meters at which the waypoint counts as reached
+    pub arrival_radius: f32,
     pub yaw_deg: f32,
     pub segment_id: u32,
 }
 
 impl Waypoint{
-    pub fn new(ned: NED, color: [u8; 3], hold_time: f32, yaw_deg: f32, segment_id: u32) -> Self {
-        Self { ned, color, hold_time, yaw_deg, segment_id }
+    pub fn new(ned: NED, color: [u8; 3], hold_time: f32, arrival_radius: f32, yaw_deg: f32, segment_id: u32) -> Self {
+        Self { ned, color, hold_time, arrival_radius, yaw_deg, segment_id }
     }
 }
\ No newline at end of file
@@ -0,0 +1,137 @@
+use mavlink::ardupilotmega::{
+    COMMAND_LONG_DATA, LED_CONTROL_DATA, MavCmd, MavFrame, MavMessage, PositionTargetTypemask,
+    SET_POSITION_TARGET_LOCAL_NED_DATA,
+};
+
+use crate::common::{led::{LED, LedAnimation}, state::NED};
+
+/// Position setpoint in the local NED frame, velocity/accel/yaw-rate ignored.
+/// Yaw is ignored too when `yaw_deg` is None.
+pub fn build_position_target_local_ned(ned: &NED, yaw_deg: Option<f32>) -> MavMessage {
+    let mut type_mask = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VX_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VY_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VZ_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+    if yaw_deg.is_none() {
+        type_mask |= PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE;
+    }
+    MavMessage::SET_POSITION_TARGET_LOCAL_NED(SET_POSITION_TARGET_LOCAL_NED_DATA {
+        x: ned.north,
+        y: ned.east,
+        z: ned.down,
+        yaw: yaw_deg.unwrap_or_default().to_radians(),
+        type_mask,
+        coordinate_frame: MavFrame::MAV_FRAME_LOCAL_NED,
+        ..Default::default()
+    })
+}
+
+/// Velocity-only setpoint in the local NED frame (m/s), everything else ignored
+pub fn build_velocity_target_local_ned(velocity: &NED) -> MavMessage {
+    let type_mask = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_X_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_Y_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_Z_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE
+        | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+    MavMessage::SET_POSITION_TARGET_LOCAL_NED(SET_POSITION_TARGET_LOCAL_NED_DATA {
+        vx: velocity.north,
+        vy: velocity.east,
+        vz: velocity.down,
+        type_mask,
+        coordinate_frame: MavFrame::MAV_FRAME_LOCAL_NED,
+        ..Default::default()
+    })
+}
+
+/// MAV_CMD_SET_MESSAGE_INTERVAL for one message id, a rate of 0 stops the message
+pub fn build_set_message_interval(message_id: u32, rate_hz: f32) -> MavMessage {
+    let interval_us = if rate_hz > 0.0 { 1_000_000.0 / rate_hz } else { -1.0 };
+    MavMessage::COMMAND_LONG(COMMAND_LONG_DATA {
+        param1: message_id as f32,
+        param2: interval_us,
+        command: MavCmd::MAV_CMD_SET_MESSAGE_INTERVAL,
+        ..Default::default()
+    })
+}
+
+/// LED_CONTROL override for all LEDs, ArduPilot needs NTF_LED_OVERRIDE=1 (MAVLink).
+/// 3 custom bytes set a solid colour, a 4th byte sets a blink rate in Hz.
+pub fn build_led_control(led: &LED) -> MavMessage {
+    let rgb = led.output_rgb();
+    let mut custom_bytes = [0u8; 24];
+    custom_bytes[..3].copy_from_slice(&rgb);
+    let custom_len = match led.animation {
+        LedAnimation::Solid => 3,
+        LedAnimation::Blink { rate_hz } => {
+            custom_bytes[3] = rate_hz;
+            4
+        }
+    };
+    MavMessage::LED_CONTROL(LED_CONTROL_DATA {
+        instance: 255,
+        custom_len,
+        custom_bytes,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_long(msg: MavMessage) -> COMMAND_LONG_DATA {
+        match msg {
+            MavMessage::COMMAND_LONG(data) => data,
+            other => panic!("Expected COMMAND_LONG, got {:?}", other),
+        }
+    }
+
+    fn led_control(msg: MavMessage) -> LED_CONTROL_DATA {
+        match msg {
+            MavMessage::LED_CONTROL(data) => data,
+            other => panic!("Expected LED_CONTROL, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn led_control_solid_sends_three_bytes() {
+        let data = led_control(build_led_control(&LED::new([255, 128, 0], 0.5, true, LedAnimation::Solid)));
+        assert_eq!(data.instance, 255);
+        assert_eq!(data.custom_len, 3);
+        assert_eq!(data.custom_bytes[..4], [128, 64, 0, 0]);
+    }
+
+    #[test]
+    fn led_control_blink_adds_the_rate_byte() {
+        let data = led_control(build_led_control(&LED::new([0, 0, 255], 1.0, true, LedAnimation::Blink { rate_hz: 4 })));
+        assert_eq!(data.custom_len, 4);
+        assert_eq!(data.custom_bytes[..4], [0, 0, 255, 4]);
+    }
+
+    #[test]
+    fn led_control_off_is_black() {
+        let data = led_control(build_led_control(&LED::new([255, 255, 255], 1.0, false, LedAnimation::Solid)));
+        assert_eq!(data.custom_bytes[..3], [0, 0, 0]);
+    }
+
+    #[test]
+    fn set_message_interval_converts_rate_to_interval() {
+        let data = command_long(build_set_message_interval(30, 50.0));
+        assert_eq!(data.command, MavCmd::MAV_CMD_SET_MESSAGE_INTERVAL);
+        assert_eq!(data.param1, 30.0);
+        assert_eq!(data.param2, 20_000.0);
+    }
+
+    #[test]
+    fn set_message_interval_zero_rate_disables() {
+        let data = command_long(build_set_message_interval(33, 0.0));
+        assert_eq!(data.param1, 33.0);
+        assert_eq!(data.param2, -1.0);
+    }
+}
@@ -1,5 +1,11 @@
+use std::{collections::BTreeMap, path::Path};
+
+use mavlink::Message;
 use serde::{Deserialize, Serialize};
 
+use crate::common::{health::HealthDebounceConfig, telemetry_output::TelemetryOutput};
+use crate::link::{mav_rate_limit::CommandRateLimit, mav_reconnect::ReconnectPolicy};
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", content = "args")]
 pub enum MavlinkConnectionType {
@@ -8,10 +14,74 @@ pub enum MavlinkConnectionType {
     Tcp(String, u32),
 }
 
+/// MAVLink v2 message signing, see https://mavlink.io/en/guide/message_signing.html
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MavSigningConfig {
+    /// 32 byte secret key as 64 hex characters
+    pub secret_key_hex: String,
+    pub link_id: u8,
+    /// Accept unsigned incoming messages instead of rejecting them
+    pub allow_unsigned: bool,
+}
+
+impl MavSigningConfig {
+    pub fn new(secret_key_hex: String, link_id: u8, allow_unsigned: bool) -> Self {
+        Self { secret_key_hex, link_id, allow_unsigned }
+    }
+
+    pub fn secret_key(&self) -> Result<[u8; 32], anyhow::Error> {
+        let hex = self.secret_key_hex.trim();
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(anyhow::anyhow!("Signing key must be 64 hex characters (32 bytes)"));
+        }
+        let mut key = [0u8; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|e| anyhow::anyhow!("Invalid signing key hex: {}", e))?;
+        }
+        Ok(key)
+    }
+
+    pub fn to_signing_config(&self) -> Result<mavlink::SigningConfig, anyhow::Error> {
+        Ok(mavlink::SigningConfig::new(self.secret_key()?, self.link_id, true, self.allow_unsigned))
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MavConfig{
     pub connection: MavlinkConnectionType,
     pub telemetry_rate_hz: u32,
+    #[serde(default)]
+    pub reconnect: ReconnectPolicy,
+    #[serde(default)]
+    pub command_rate_limit: CommandRateLimit,
+    /// System id we send as, some autopilots only accept commands from their GCS id
+    #[serde(default = "default_source_system_id")]
+    pub source_system_id: u8,
+    #[serde(default)]
+    pub source_component_id: u8,
+    /// Only accept received messages from this system id (None accepts all)
+    #[serde(default)]
+    pub recv_system_id: Option<u8>,
+    /// Only accept received messages from this component id (None accepts all)
+    #[serde(default)]
+    pub recv_component_id: Option<u8>,
+    /// Unsigned when None
+    #[serde(default)]
+    pub signing: Option<MavSigningConfig>,
+    #[serde(default)]
+    pub health_debounce: HealthDebounceConfig,
+    /// Frame / units used when logging position telemetry
+    #[serde(default)]
+    pub telemetry_output: TelemetryOutput,
+    /// Message id -> rate in Hz, requested with SET_MESSAGE_INTERVAL.
+    /// When empty all streams are requested at telemetry_rate_hz instead.
+    #[serde(default)]
+    pub message_rates: BTreeMap<u32, f32>,
+}
+
+fn default_source_system_id() -> u8 {
+    255
 }
 
 impl Default for MavConfig{
@@ -26,7 +96,91 @@ impl Default for MavConfig{
 
 impl MavConfig {
     pub fn new(connection: MavlinkConnectionType, telemetry_rate_hz: u32) -> Self {
-        Self { connection, telemetry_rate_hz }
+        Self {
+            connection,
+            telemetry_rate_hz,
+            reconnect: ReconnectPolicy::default(),
+            command_rate_limit: CommandRateLimit::default(),
+            source_system_id: default_source_system_id(),
+            source_component_id: 0,
+            recv_system_id: None,
+            recv_component_id: None,
+            signing: None,
+            health_debounce: HealthDebounceConfig::default(),
+            telemetry_output: TelemetryOutput::default(),
+            message_rates: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Could not read config {}: {}", path.display(), e))?;
+        serde_yaml::from_str(&text)
+            .map_err(|e| anyhow::anyhow!("Could not parse config {}: {}", path.display(), e))
+    }
+
+    /// Copy that is safe to print, the signing key is replaced with `<redacted>`
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        if let Some(signing) = &mut config.signing {
+            signing.secret_key_hex = "<redacted>".to_string();
+        }
+        config
+    }
+
+    /// Checks the config without connecting to anything, returns every problem found
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match &self.connection {
+            MavlinkConnectionType::Serial(path, baud) => {
+                if path.trim().is_empty() {
+                    problems.push("connection: serial path is empty".to_string());
+                }
+                if *baud == 0 {
+                    problems.push("connection: serial baud rate must be positive".to_string());
+                }
+            }
+            MavlinkConnectionType::Udp(address, port) | MavlinkConnectionType::Tcp(address, port) => {
+                if address.trim().is_empty() {
+                    problems.push("connection: address is empty".to_string());
+                }
+                if *port == 0 || *port > u16::MAX as u32 {
+                    problems.push(format!("connection: port {} is out of range", port));
+                }
+            }
+        }
+        if self.telemetry_rate_hz == 0 {
+            problems.push("telemetry_rate_hz must be positive".to_string());
+        }
+        if self.source_system_id == 0 {
+            problems.push("source_system_id 0 is the broadcast id".to_string());
+        }
+        if self.reconnect.max_attempts == Some(0) {
+            problems.push("reconnect.max_attempts must be at least 1".to_string());
+        }
+        if self.reconnect.initial_backoff_ms > self.reconnect.max_backoff_ms {
+            problems.push(format!(
+                "reconnect.initial_backoff_ms ({}) is above max_backoff_ms ({})",
+                self.reconnect.initial_backoff_ms, self.reconnect.max_backoff_ms
+            ));
+        }
+        if self.command_rate_limit.max_commands_per_sec == 0 {
+            problems.push("command_rate_limit.max_commands_per_sec must be positive".to_string());
+        }
+        if self.health_debounce.flap_transitions == 0 {
+            problems.push("health_debounce.flap_transitions must be positive".to_string());
+        }
+        if let Some(signing) = &self.signing
+            && let Err(e) = signing.secret_key()
+        {
+            problems.push(format!("signing: {}", e));
+        }
+        for (message_id, rate_hz) in &self.message_rates {
+            if !rate_hz.is_finite() || *rate_hz < 0.0 {
+                problems.push(format!("message_rates: invalid rate {} for message {}", rate_hz, message_id));
+            }
+        }
+        problems
     }
 
     pub fn connection_string(&self) -> String {
@@ -37,6 +191,20 @@ impl MavConfig {
         }
     }
 
+    /// Header for outgoing messages, the connection fills in the sequence number
+    pub fn send_header(&self) -> mavlink::MavHeader {
+        mavlink::MavHeader {
+            system_id: self.source_system_id,
+            component_id: self.source_component_id,
+            sequence: 0,
+        }
+    }
+
+    pub fn accepts_source(&self, header: &mavlink::MavHeader) -> bool {
+        self.recv_system_id.is_none_or(|id| id == header.system_id)
+            && self.recv_component_id.is_none_or(|id| id == header.component_id)
+    }
+
     pub fn get_port(&self) -> u32 {
         match &self.connection {
             MavlinkConnectionType::Serial(_, port) => *port,
@@ -45,3 +213,114 @@ impl MavConfig {
         }
     }
 }
+
+/// Parses `<MESSAGE>=<HZ>` where MESSAGE is a message name (ATTITUDE) or numeric id (30)
+pub fn parse_message_rate(value: &str) -> Result<(u32, f32), String> {
+    let (message, rate) = value
+        .split_once('=')
+        .ok_or(format!("Expected <MESSAGE>=<HZ>, got '{}'", value))?;
+    let message_id = match message.parse::<u32>() {
+        Ok(message_id) => message_id,
+        Err(_) => mavlink::ardupilotmega::MavMessage::message_id_from_name(message)
+            .ok_or(format!("Unknown MAVLink message '{}'", message))?,
+    };
+    let rate_hz = rate
+        .parse::<f32>()
+        .map_err(|e| format!("Invalid rate '{}': {}", rate, e))?;
+    if rate_hz < 0.0 {
+        return Err(format!("Rate must not be negative, got {}", rate_hz));
+    }
+    Ok((message_id, rate_hz))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(MavConfig::default().validate().is_empty());
+    }
+
+    #[test]
+    fn partial_reconnect_block_parses() {
+        let yaml = "connection:\n  type: Tcp\n  args: [127.0.0.1, 5760]\ntelemetry_rate_hz: 20\nreconnect:\n  max_attempts: 5\n";
+        let config: MavConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.reconnect.max_attempts, Some(5));
+        assert_eq!(config.reconnect.on_exhausted, crate::link::mav_reconnect::ExitOrContinue::Continue);
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_port_zero() {
+        let config = MavConfig::new(MavlinkConnectionType::Udp("0.0.0.0".to_string(), 0), 20);
+        assert_eq!(config.validate(), vec!["connection: port 0 is out of range".to_string()]);
+    }
+
+    #[test]
+    fn validate_rejects_zero_rate() {
+        let config = MavConfig::new(MavlinkConnectionType::Tcp("127.0.0.1".to_string(), 5760), 0);
+        assert_eq!(config.validate(), vec!["telemetry_rate_hz must be positive".to_string()]);
+    }
+
+    #[test]
+    fn validate_rejects_bad_signing_key() {
+        let mut config = MavConfig {
+            signing: Some(MavSigningConfig::new(TEST_KEY.replace("00", "zz"), 0, false)),
+            ..MavConfig::default()
+        };
+        let problems = config.validate();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("signing: Invalid signing key hex"), "{:?}", problems);
+
+        config.signing = Some(MavSigningConfig::new("abcd".to_string(), 0, false));
+        assert_eq!(config.validate().len(), 1);
+
+        config.signing = Some(MavSigningConfig::new(TEST_KEY.to_string(), 0, false));
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_backoff_min_above_max() {
+        let mut config = MavConfig::default();
+        config.reconnect.initial_backoff_ms = 5000;
+        config.reconnect.max_backoff_ms = 1000;
+        assert_eq!(
+            config.validate(),
+            vec!["reconnect.initial_backoff_ms (5000) is above max_backoff_ms (1000)".to_string()]
+        );
+    }
+
+    #[test]
+    fn redacted_hides_the_signing_key() {
+        let config = MavConfig {
+            signing: Some(MavSigningConfig::new(TEST_KEY.to_string(), 1, false)),
+            ..MavConfig::default()
+        };
+        let yaml = serde_yaml::to_string(&config.redacted()).unwrap();
+        assert!(!yaml.contains(TEST_KEY));
+        assert!(yaml.contains("<redacted>"));
+        assert_eq!(config.signing.unwrap().secret_key_hex, TEST_KEY);
+    }
+
+    #[test]
+    fn parse_message_rate_by_name() {
+        assert_eq!(parse_message_rate("ATTITUDE=50"), Ok((30, 50.0)));
+    }
+
+    #[test]
+    fn parse_message_rate_by_numeric_id() {
+        assert_eq!(parse_message_rate("30=10"), Ok((30, 10.0)));
+        assert_eq!(parse_message_rate("33=0"), Ok((33, 0.0)));
+    }
+
+    #[test]
+    fn parse_message_rate_rejects_bad_input() {
+        assert!(parse_message_rate("ATTITUDE=-1").is_err());
+        assert!(parse_message_rate("ATTITUDE").is_err());
+        assert!(parse_message_rate("NOT_A_MESSAGE=5").is_err());
+        assert!(parse_message_rate("ATTITUDE=fast").is_err());
+    }
+}
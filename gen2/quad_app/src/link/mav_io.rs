@@ -1,7 +1,7 @@
-use crate::link::{mav_config::MavConfig, mav_queues::MavQueues};
+use crate::link::{mav_builders::build_set_message_interval, mav_config::MavConfig, mav_queues::MavQueues, mav_reconnect::ExitOrContinue};
 
 
-use log::{debug, error, info, trace};
+use log::{debug, error, info, trace, warn};
 use mavlink::{ardupilotmega::MavMessage};
 use std::{
     sync::{
@@ -19,30 +19,34 @@ pub struct MavIO{
     mav_con: Option<Box<dyn mavlink::MavConnection<MavlinkMessageType> + Send + Sync>>,
     enabled: AtomicBool,
     queues: MavQueues,
+    /// Parsed once up front, a bad key is a config error and not worth retrying
+    signing: Option<mavlink::SigningConfig>,
 }
 
 impl MavIO{
-    pub fn new(config: MavConfig, queues: MavQueues) -> Self {
-        Self { config, mav_con: None, enabled: AtomicBool::new(false), queues }
+    pub fn new(config: MavConfig, queues: MavQueues) -> Result<Self, anyhow::Error> {
+        let signing = match &config.signing {
+            Some(signing) => Some(signing.to_signing_config()?),
+            None => None,
+        };
+        Ok(Self { config, mav_con: None, enabled: AtomicBool::new(false), queues, signing })
     }   
 
     pub fn start(&mut self) -> Result<(), anyhow::Error> {
         self.enabled.store(true, Ordering::Relaxed);
-        info!("SkyCanvas // MavIO // Connecting to MAVLink: {}", self.config.connection_string());
-        let mut mav_con = mavlink::connect::<MavlinkMessageType>(&self.config.connection_string().as_str())?;
-        self.mav_con = Some(Box::new(mav_con));
-
-        info!("SkyCanvas // MavIO // Setting protocol version to V2");
-        let mav_con = self.mav_con.as_mut().unwrap();
-        mav_con.set_protocol_version(mavlink::MavlinkVersion::V2);
-        self.send_request_stream()?;
+        // The autopilot / SITL may still be booting, so the first connect goes through the policy too
+        self.connect_with_retry()?;
         info!("SkyCanvas // MavIO // Starting IO Tick loop");
         while self.enabled.load(Ordering::Relaxed) {
 
-            //  First on each tick - send out any commands that are sent to IO by the quad app
-            self.tick_send()?;
-            // 2. Recv any messages from the MAVLink connection
-            self.tick_recv()?;
+            // Send out any commands queued by the quad app, then recv from the MAVLink connection
+            // IO errors drop the connection and hand over to the reconnect policy,
+            // a frame that fails to parse is skipped in tick_recv
+            if let Err(e) = self.tick_send().and_then(|_| self.tick_recv()) {
+                error!("SkyCanvas // MavIO // Link error, reconnecting: {}", e);
+                self.mav_con = None;
+                self.connect_with_retry()?;
+            }
 
             // For now rate limit by adding 10ms
             thread::sleep(Duration::from_millis(10));
@@ -51,8 +55,43 @@ impl MavIO{
         Ok(())
     }
 
+    fn connect(&mut self) -> Result<(), anyhow::Error> {
+        info!("SkyCanvas // MavIO // Connecting to MAVLink: {}", self.config.connection_string());
+        let mav_con = mavlink::connect::<MavlinkMessageType>(&self.config.connection_string().as_str())?;
+        self.mav_con = Some(Box::new(mav_con));
+
+        info!("SkyCanvas // MavIO // Setting protocol version to V2");
+        let mav_con = self.mav_con.as_mut().unwrap();
+        mav_con.set_protocol_version(mavlink::MavlinkVersion::V2);
+        if let (Some(signing), Some(signing_config)) = (&self.signing, &self.config.signing) {
+            info!("SkyCanvas // MavIO // Enabling message signing on link {}", signing_config.link_id);
+            mav_con.setup_signing(Some(signing.clone()));
+        }
+        if self.config.message_rates.is_empty() {
+            self.send_request_stream()?;
+        } else {
+            self.send_message_intervals()?;
+        }
+        Ok(())
+    }
+
+    fn connect_with_retry(&mut self) -> Result<(), anyhow::Error> {
+        let policy = self.config.reconnect.clone();
+        let result = policy.retry("SkyCanvas // MavIO // Connect", |attempt| {
+            info!("SkyCanvas // MavIO // Connect attempt {}", attempt);
+            self.connect()
+        });
+        if let Err(e) = &result {
+            error!("SkyCanvas // MavIO // {}", e);
+            if policy.on_exhausted == ExitOrContinue::Exit {
+                std::process::exit(1);
+            }
+        }
+        result
+    }
+
     fn tick_send(&mut self) -> Result<(), anyhow::Error> {
-        let commands = match self.queues.recv() {
+        let commands = match self.queues.recv_outbound() {
             Ok(Some(msg)) => msg,
             Ok(None) => return Ok(()),
             Err(e) => {
@@ -61,7 +100,7 @@ impl MavIO{
             }
         };
         let mav_con = self.mav_con.as_ref().unwrap();
-        mav_con.send(&mavlink::MavHeader::default(), &commands)?;
+        mav_con.send(&self.config.send_header(), &commands)?;
         Ok(())
     }
 
@@ -69,10 +108,14 @@ impl MavIO{
         let mav_con = self.mav_con.as_ref().unwrap();
         match mav_con.try_recv(){
             Ok(msg) => {
+                if !self.config.accepts_source(&msg.0) {
+                    trace!("SkyCanvas // MavIO // Dropping message from system {} component {}", msg.0.system_id, msg.0.component_id);
+                    return Ok(());
+                }
                 //info!("SkyCanvas // MavIO // Received message: {:#?}", msg);
              //   let message_type = crate::common::mavlink_helpers::mavlink_msg_type_str(&msg.1.clone());
                 //trace!("SkyCanvas // MavIO // Received message: {}", message_type);
-                self.queues.send(msg.1)?;
+                self.queues.send_inbound(msg.1)?;
                 Ok(())
             },
             Err(mavlink::error::MessageReadError::Io(e)) => {
@@ -86,8 +129,9 @@ impl MavIO{
                 }
             },
             Err(mavlink::error::MessageReadError::Parse(e)) => {
-                error!("SkyCanvas // MavIO // Parse Error: {}", e);
-                Err(anyhow::anyhow!("Parse Error: {}", e))
+                // One bad frame (unknown id / enum value) is not a reason to drop the link
+                warn!("SkyCanvas // MavIO // Skipping frame that failed to parse: {}", e);
+                Ok(())
             }
         }
     }
@@ -103,7 +147,157 @@ impl MavIO{
             start_stop: 1,
         });
         info!("SkyCanvas // MavIO // Sending request stream: {:#?}", packet);
-        self.queues.send(packet)?;
+        self.queues.send_outbound(packet)?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn send_message_intervals(&self) -> Result<(), anyhow::Error> {
+        for (message_id, rate_hz) in &self.config.message_rates {
+            info!("SkyCanvas // MavIO // Requesting message {} at {}Hz", message_id, rate_hz);
+            self.queues.send_outbound(build_set_message_interval(*message_id, *rate_hz))?;
+        }
+        Ok(())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use mavlink::{MavHeader, peek_reader::PeekReader};
+
+    use super::*;
+    use crate::link::mav_config::MavlinkConnectionType;
+
+    /// Everything the vehicle side receives until the link goes quiet
+    fn drain_vehicle(reader: &mut PeekReader<std::net::TcpStream>) -> Vec<MavMessage> {
+        reader.reader_mut().set_read_timeout(Some(Duration::from_millis(300))).unwrap();
+        let mut received = Vec::new();
+        while let Ok((_, msg)) = mavlink::read_v2_msg::<MavMessage, _>(reader) {
+            received.push(msg);
+        }
+        received
+    }
+
+    #[test]
+    fn commands_reach_the_vehicle_while_telemetry_flows() {
+        use crate::{
+            common::{
+                commands::{QuadAppCommand, QuadAppCommandType},
+                context::QuadAppContext,
+                log_rerun::RerunSink,
+                state::NED,
+            },
+            link::{
+                mav_mode::ArduMode,
+                mav_rate_limit::CommandRateLimit,
+                mav_tasks::MavTasks,
+                tasks::{mavtask_heartbeat::MavTaskHeartbeat, mavtask_send::MavTaskSend},
+            },
+        };
+        use mavlink::ardupilotmega::{HEARTBEAT_DATA, MavAutopilot};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port() as u32;
+        let config = MavConfig::new(MavlinkConnectionType::Tcp("127.0.0.1".to_string(), port), 20);
+        let queues = MavQueues::new();
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut mav_io = MavIO::new(config, queues.clone()).unwrap();
+        let mut tasks = MavTasks::new(queues.clone(), context.clone());
+        tasks.add_task(Box::new(MavTaskHeartbeat::new()));
+        tasks.add_task(Box::new(MavTaskSend::new(CommandRateLimit::default())));
+
+        mav_io.connect().unwrap();
+        let (mut vehicle, _) = listener.accept().unwrap();
+        let mut reader = PeekReader::new(vehicle.try_clone().unwrap());
+        drain_vehicle(&mut reader);
+
+        let heartbeat = MavMessage::HEARTBEAT(HEARTBEAT_DATA {
+            custom_mode: ArduMode::Guided.to_u32(),
+            autopilot: MavAutopilot::MAV_AUTOPILOT_ARDUPILOTMEGA,
+            ..Default::default()
+        });
+        let vehicle_header = MavHeader { system_id: 1, component_id: 1, sequence: 0 };
+        context.commands.lock().unwrap().push_back(QuadAppCommand::new(QuadAppCommandType::Position(
+            NED::new(1.0, 2.0, -3.0),
+            None,
+        )));
+        for _ in 0..20 {
+            mavlink::write_v2_msg(&mut vehicle, vehicle_header, &heartbeat).unwrap();
+            thread::sleep(Duration::from_millis(5));
+            mav_io.tick_send().unwrap();
+            mav_io.tick_recv().unwrap();
+            tasks.tick().unwrap();
+        }
+
+        // Telemetry went to the tasks
+        assert_eq!(context.state.read().unwrap().mode, Some(ArduMode::Guided));
+        // The setpoint went to the vehicle, and nothing it sent came back
+        let received = drain_vehicle(&mut reader);
+        let setpoints = received.iter().filter(|msg| matches!(msg, MavMessage::SET_POSITION_TARGET_LOCAL_NED(_))).count();
+        assert_eq!(setpoints, 1, "{:?}", received);
+        assert!(!received.iter().any(|msg| matches!(msg, MavMessage::HEARTBEAT(_))), "{:?}", received);
+    }
+
+    #[test]
+    fn message_intervals_are_sent_on_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port() as u32;
+        let mut config = MavConfig::new(MavlinkConnectionType::Tcp("127.0.0.1".to_string(), port), 20);
+        config.message_rates = [(30, 50.0), (33, 10.0)].into_iter().collect();
+
+        let mut mav_io = MavIO::new(config, MavQueues::new()).unwrap();
+        mav_io.connect().unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        mav_io.tick_send().unwrap();
+        mav_io.tick_send().unwrap();
+
+        let intervals: Vec<(f32, f32)> = drain_vehicle(&mut PeekReader::new(stream))
+            .into_iter()
+            .filter_map(|msg| match msg {
+                MavMessage::COMMAND_LONG(data) => Some((data.param1, data.param2)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(intervals, vec![(30.0, 20_000.0), (33.0, 100_000.0)]);
+    }
+
+    #[test]
+    fn malformed_signing_key_fails_before_connecting() {
+        let config = MavConfig {
+            signing: Some(crate::link::mav_config::MavSigningConfig::new("not hex".to_string(), 0, false)),
+            ..MavConfig::default()
+        };
+        assert!(MavIO::new(config, MavQueues::new()).is_err());
+    }
+
+    #[test]
+    fn sent_messages_carry_the_configured_ids() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port() as u32;
+        let mut config = MavConfig::new(MavlinkConnectionType::Tcp("127.0.0.1".to_string(), port), 20);
+        config.source_system_id = 42;
+        config.source_component_id = 191;
+
+        let queues = MavQueues::new();
+        let mut mav_io = MavIO::new(config, queues.clone()).unwrap();
+        mav_io.connect().unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut reader = PeekReader::new(stream);
+
+        // Stream request queued by connect()
+        mav_io.tick_send().unwrap();
+        let (header, msg): (MavHeader, MavMessage) = mavlink::read_v2_msg(&mut reader).unwrap();
+        #[allow(deprecated)]
+        let is_request_stream = matches!(msg, MavMessage::REQUEST_DATA_STREAM(_));
+        assert!(is_request_stream);
+        assert_eq!((header.system_id, header.component_id), (42, 191));
+
+        // Command queued by the app
+        queues.send_outbound(build_set_message_interval(30, 10.0)).unwrap();
+        mav_io.tick_send().unwrap();
+        let (header, msg): (MavHeader, MavMessage) = mavlink::read_v2_msg(&mut reader).unwrap();
+        assert!(matches!(msg, MavMessage::COMMAND_LONG(_)));
+        assert_eq!((header.system_id, header.component_id), (42, 191));
+    }
+}
@@ -2,30 +2,48 @@ use mavlink::ardupilotmega::MavMessage;
 
 pub type MavlinkMessageType = MavMessage;
 
+/// Channels between the MavIO and MavTasks threads.
+/// Outbound: MavTasks -> MavIO -> vehicle. Inbound: vehicle -> MavIO -> MavTasks.
 #[derive(Debug, Clone)]
 pub struct MavQueues{
-    tx: crossbeam_channel::Sender<MavlinkMessageType>,
-    rx: crossbeam_channel::Receiver<MavlinkMessageType>,
+    outbound_tx: crossbeam_channel::Sender<MavlinkMessageType>,
+    outbound_rx: crossbeam_channel::Receiver<MavlinkMessageType>,
+    inbound_tx: crossbeam_channel::Sender<MavlinkMessageType>,
+    inbound_rx: crossbeam_channel::Receiver<MavlinkMessageType>,
 }
 
 impl MavQueues {
     pub fn new() -> Self {
-        let (tx, rx) = crossbeam_channel::bounded(1000); 
-        Self { tx, rx }
+        let (outbound_tx, outbound_rx) = crossbeam_channel::bounded(1000);
+        let (inbound_tx, inbound_rx) = crossbeam_channel::bounded(1000);
+        Self { outbound_tx, outbound_rx, inbound_tx, inbound_rx }
     }
 
-    pub fn send(&self, message: MavlinkMessageType) -> Result<(), anyhow::Error> {
-        self.tx.send(message)?;
+    /// Queue a message to be sent to the vehicle
+    pub fn send_outbound(&self, message: MavlinkMessageType) -> Result<(), anyhow::Error> {
+        self.outbound_tx.send(message)?;
         Ok(())
     }
 
-    pub fn recv(&self) -> Result<Option<MavlinkMessageType>, anyhow::Error> {
-        let message = self.rx.try_recv();
-        match message {
+    pub fn recv_outbound(&self) -> Result<Option<MavlinkMessageType>, anyhow::Error> {
+        Self::try_recv(&self.outbound_rx)
+    }
+
+    /// Hand a message received from the vehicle to the tasks
+    pub fn send_inbound(&self, message: MavlinkMessageType) -> Result<(), anyhow::Error> {
+        self.inbound_tx.send(message)?;
+        Ok(())
+    }
+
+    pub fn recv_inbound(&self) -> Result<Option<MavlinkMessageType>, anyhow::Error> {
+        Self::try_recv(&self.inbound_rx)
+    }
+
+    fn try_recv(rx: &crossbeam_channel::Receiver<MavlinkMessageType>) -> Result<Option<MavlinkMessageType>, anyhow::Error> {
+        match rx.try_recv() {
             Ok(message) => Ok(Some(message)),
             Err(crossbeam_channel::TryRecvError::Empty) => Ok(None),
             Err(crossbeam_channel::TryRecvError::Disconnected) => Err(anyhow::anyhow!("Channel disconnected")),
         }
     }
 }
-
@@ -0,0 +1,148 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use mavlink::ardupilotmega::{MavCmd, MavMessage};
+use serde::{Deserialize, Serialize};
+
+use crate::common::mavlink_helpers::mavlink_msg_type_str;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommandRateLimit {
+    pub max_commands_per_sec: u32,
+    /// Minimum time between two identical messages, identical repeats inside it are dropped
+    pub min_identical_interval_ms: u64,
+}
+
+impl Default for CommandRateLimit {
+    fn default() -> Self {
+        Self {
+            max_commands_per_sec: 50,
+            min_identical_interval_ms: 20,
+        }
+    }
+}
+
+/// Sliding one-second window limiter for outgoing commands.
+/// Disarm and flight termination always pass.
+pub struct CommandRateLimiter {
+    config: CommandRateLimit,
+    sent: VecDeque<Instant>,
+    last_by_type: HashMap<String, (MavMessage, Instant)>,
+}
+
+impl CommandRateLimiter {
+    pub fn new(config: CommandRateLimit) -> Self {
+        Self {
+            config,
+            sent: VecDeque::new(),
+            last_by_type: HashMap::new(),
+        }
+    }
+
+    /// Returns Ok if `msg` may be sent at `now` and records it, otherwise the reason it was dropped
+    pub fn check(&mut self, msg: &MavMessage, now: Instant) -> Result<(), String> {
+        if is_safety_critical(msg) {
+            return Ok(());
+        }
+
+        while let Some(sent_at) = self.sent.front() {
+            if now.duration_since(*sent_at) >= Duration::from_secs(1) {
+                self.sent.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.sent.len() as u32 >= self.config.max_commands_per_sec {
+            return Err(format!(
+                "rate cap of {} commands/s reached",
+                self.config.max_commands_per_sec
+            ));
+        }
+
+        let msg_type = mavlink_msg_type_str(msg);
+        if let Some((last_msg, last_at)) = self.last_by_type.get(&msg_type) {
+            let min_interval = Duration::from_millis(self.config.min_identical_interval_ms);
+            if last_msg == msg && now.duration_since(*last_at) < min_interval {
+                return Err(format!(
+                    "identical {} sent less than {}ms ago",
+                    msg_type, self.config.min_identical_interval_ms
+                ));
+            }
+        }
+
+        self.sent.push_back(now);
+        self.last_by_type.insert(msg_type, (msg.clone(), now));
+        Ok(())
+    }
+}
+
+fn is_safety_critical(msg: &MavMessage) -> bool {
+    match msg {
+        MavMessage::COMMAND_LONG(data) => match data.command {
+            MavCmd::MAV_CMD_COMPONENT_ARM_DISARM => data.param1 == 0.0,
+            MavCmd::MAV_CMD_DO_FLIGHTTERMINATION => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mavlink::ardupilotmega::COMMAND_LONG_DATA;
+
+    use super::*;
+
+    fn command(command: MavCmd, param1: f32) -> MavMessage {
+        MavMessage::COMMAND_LONG(COMMAND_LONG_DATA { command, param1, ..Default::default() })
+    }
+
+    fn limiter(max_commands_per_sec: u32, min_identical_interval_ms: u64) -> CommandRateLimiter {
+        CommandRateLimiter::new(CommandRateLimit { max_commands_per_sec, min_identical_interval_ms })
+    }
+
+    #[test]
+    fn burst_is_capped_per_second() {
+        let mut limiter = limiter(5, 0);
+        let now = Instant::now();
+        let passed = (0..20)
+            .filter(|i| limiter.check(&command(MavCmd::MAV_CMD_DO_SET_SERVO, *i as f32), now).is_ok())
+            .count();
+        assert_eq!(passed, 5);
+
+        // The window slides, a second later the cap is available again
+        let later = now + Duration::from_secs(1);
+        assert!(limiter.check(&command(MavCmd::MAV_CMD_DO_SET_SERVO, 100.0), later).is_ok());
+    }
+
+    #[test]
+    fn identical_messages_need_min_interval() {
+        let mut limiter = limiter(100, 20);
+        let now = Instant::now();
+        let msg = command(MavCmd::MAV_CMD_DO_SET_SERVO, 1.0);
+        assert!(limiter.check(&msg, now).is_ok());
+        assert!(limiter.check(&msg, now + Duration::from_millis(10)).is_err());
+        // A different message of the same type is not a repeat
+        assert!(limiter.check(&command(MavCmd::MAV_CMD_DO_SET_SERVO, 2.0), now + Duration::from_millis(10)).is_ok());
+        assert!(limiter.check(&msg, now + Duration::from_millis(40)).is_ok());
+    }
+
+    #[test]
+    fn disarm_and_termination_bypass_the_limit() {
+        let mut limiter = limiter(1, 1000);
+        let now = Instant::now();
+        assert!(limiter.check(&command(MavCmd::MAV_CMD_DO_SET_SERVO, 1.0), now).is_ok());
+        assert!(limiter.check(&command(MavCmd::MAV_CMD_DO_SET_SERVO, 2.0), now).is_err());
+
+        let disarm = command(MavCmd::MAV_CMD_COMPONENT_ARM_DISARM, 0.0);
+        let terminate = command(MavCmd::MAV_CMD_DO_FLIGHTTERMINATION, 1.0);
+        for _ in 0..3 {
+            assert!(limiter.check(&disarm, now).is_ok());
+            assert!(limiter.check(&terminate, now).is_ok());
+        }
+        // Arming is not safety critical
+        assert!(limiter.check(&command(MavCmd::MAV_CMD_COMPONENT_ARM_DISARM, 1.0), now).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use std::{thread, time::Duration};
+
+use clap::ValueEnum;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExitOrContinue {
+    /// Exit the process with a non-zero code (CI / tests)
+    Exit,
+    /// Give up on the link but keep the app running
+    #[default]
+    Continue,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// None retries forever
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    #[serde(default)]
+    pub on_exhausted: ExitOrContinue,
+    /// Delay after the first failed attempt, doubled after each further failure
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_backoff_ms() -> u64 {
+    10_000
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new(None, ExitOrContinue::default())
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn new(max_attempts: Option<u32>, on_exhausted: ExitOrContinue) -> Self {
+        Self {
+            max_attempts,
+            on_exhausted,
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+
+    pub fn with_backoff(mut self, initial_backoff_ms: u64, max_backoff_ms: u64) -> Self {
+        self.initial_backoff_ms = initial_backoff_ms;
+        self.max_backoff_ms = max_backoff_ms;
+        self
+    }
+
+    /// Delay to wait after the given (1-based) failed attempt
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+
+    pub fn allows_attempt(&self, attempt: u32) -> bool {
+        match self.max_attempts {
+            Some(max_attempts) => attempt <= max_attempts,
+            None => true,
+        }
+    }
+
+    /// Calls `f` until it succeeds or the policy runs out of attempts, backing off between failures.
+    /// `f` is given the 1-based attempt number. Returns the last error once exhausted.
+    pub fn retry<T>(
+        &self,
+        name: &str,
+        mut f: impl FnMut(u32) -> Result<T, anyhow::Error>,
+    ) -> Result<T, anyhow::Error> {
+        let mut attempt = 1;
+        loop {
+            match f(attempt) {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    if !self.allows_attempt(attempt + 1) {
+                        return Err(anyhow::anyhow!(
+                            "{} // Gave up after {} attempts: {}",
+                            name,
+                            attempt,
+                            e
+                        ));
+                    }
+                    let backoff = self.backoff(attempt);
+                    warn!("{} // Attempt {} failed, retrying in {:?}: {}", name, attempt, backoff, e);
+                    thread::sleep(backoff);
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_block_uses_defaults() {
+        let policy: ReconnectPolicy = serde_yaml::from_str("max_attempts: 3").unwrap();
+        assert_eq!(policy.max_attempts, Some(3));
+        assert_eq!(policy.on_exhausted, ExitOrContinue::Continue);
+        assert_eq!(policy.initial_backoff_ms, 500);
+        assert_eq!(policy.max_backoff_ms, 10_000);
+
+        let policy: ReconnectPolicy = serde_yaml::from_str("on_exhausted: Exit").unwrap();
+        assert_eq!(policy.max_attempts, None);
+        assert_eq!(policy.on_exhausted, ExitOrContinue::Exit);
+    }
+
+    fn no_sleep_policy(max_attempts: Option<u32>) -> ReconnectPolicy {
+        ReconnectPolicy::new(max_attempts, ExitOrContinue::Continue).with_backoff(0, 0)
+    }
+
+    #[test]
+    fn retry_gives_up_after_max_attempts() {
+        let policy = no_sleep_policy(Some(3));
+        let mut calls = Vec::new();
+        let result: Result<(), _> = policy.retry("test", |attempt| {
+            calls.push(attempt);
+            Err(anyhow::anyhow!("connection refused"))
+        });
+        assert_eq!(calls, vec![1, 2, 3]);
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("Gave up after 3 attempts"), "{}", error);
+        assert!(error.contains("connection refused"), "{}", error);
+    }
+
+    #[test]
+    fn retry_returns_first_success() {
+        let policy = no_sleep_policy(Some(5));
+        let mut calls = 0;
+        let result = policy.retry("test", |attempt| {
+            calls += 1;
+            if attempt < 2 { Err(anyhow::anyhow!("not yet")) } else { Ok(attempt) }
+        });
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn allows_attempt_respects_max() {
+        let policy = no_sleep_policy(Some(2));
+        assert!(policy.allows_attempt(1));
+        assert!(policy.allows_attempt(2));
+        assert!(!policy.allows_attempt(3));
+        assert!(no_sleep_policy(None).allows_attempt(u32::MAX));
+    }
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let policy = ReconnectPolicy::default().with_backoff(100, 1000);
+        let backoffs: Vec<u64> = (1..=6).map(|attempt| policy.backoff(attempt).as_millis() as u64).collect();
+        assert_eq!(backoffs, vec![100, 200, 400, 800, 1000, 1000]);
+        // Large attempt counts saturate instead of overflowing
+        assert_eq!(policy.backoff(200).as_millis(), 1000);
+    }
+}
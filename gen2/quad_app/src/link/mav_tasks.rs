@@ -38,13 +38,10 @@ impl MavTasks{
         Ok(())
     }
 
+    /// One pass over received messages and app commands, `start` calls this until disabled
     pub fn tick(&mut self) -> Result<(), anyhow::Error> {
-        if !self.enabled.load(Ordering::Relaxed) {
-            return Ok(());
-        }
-
         // First read for for incoming messages
-        let messages = self.queues.recv()?;
+        let messages = self.queues.recv_inbound()?;
         if let Some(message) = messages {
             self.process_message(message)?;
         }
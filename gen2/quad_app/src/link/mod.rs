@@ -4,6 +4,9 @@ pub mod tasks;
 pub mod mav_queues;
 pub mod mav_config;
 pub mod mav_mode;
+pub mod mav_reconnect;
+pub mod mav_rate_limit;
+pub mod mav_builders;
 
 use mav_io::MavIO;
 use mav_tasks::MavTasks;
@@ -13,43 +16,46 @@ use mav_mode::ArduMode;
 use log::info;
 use std::sync::mpsc;
 
-use crate::{common::context::QuadAppContext, link::{mav_queues::MavQueues, tasks::{MavTaskTrait, mavtask_health::MavTaskHealth, mavtask_lla::MavTaskLla, mavtask_local_ned::MavTaskLocalNed, mavtask_print::MavTaskPrint, mavtask_send::MavTaskSend, mavtask_status_text::MavTaskStatusText}}};
+use crate::{common::context::QuadAppContext, link::{mav_queues::MavQueues, tasks::{MavTaskTrait, mavtask_health::MavTaskHealth, mavtask_heartbeat::MavTaskHeartbeat, mavtask_lla::MavTaskLla, mavtask_local_ned::MavTaskLocalNed, mavtask_print::MavTaskPrint, mavtask_send::MavTaskSend, mavtask_status_text::MavTaskStatusText}}};
 pub struct QuadLink{
 
 
     queues: MavQueues,
     config: MavConfig,
+    /// Built in new so config errors (e.g. a malformed signing key) fail before the connect retry loop
+    io: Option<MavIO>,
 }
 
 impl QuadLink{
-    pub fn new(config: MavConfig) -> Self {
+    pub fn new(config: MavConfig) -> Result<Self, anyhow::Error> {
         let queues = MavQueues::new();
-  
-        Self {
+        let io = MavIO::new(config.clone(), queues.clone())?;
+        Ok(Self {
             queues,
             config,
-        }
+            io: Some(io),
+        })
     }
 
     pub fn start(&mut self, context: &QuadAppContext) -> Result<(), anyhow::Error> {
         info!("SkyCanvas // QuadLink // Starting");
-        let config = self.config.clone();
-        let queues = self.queues.clone();
-        let io_handle = std::thread::spawn(move || {
-            let mut io = MavIO::new(config.clone(), queues.clone());
-            io.start()
-        });
+        let mut io = self.io.take().ok_or(anyhow::anyhow!("SkyCanvas // QuadLink // Already started"))?;
+        let io_handle = std::thread::spawn(move || io.start());
 
         let queues = self.queues.clone();
         let context = context.clone();
+        let command_rate_limit = self.config.command_rate_limit.clone();
+        let health_debounce = self.config.health_debounce.clone();
+        let telemetry_output = self.config.telemetry_output;
         let tasks_handle = std::thread::spawn(move || {
             let mut tasks = MavTasks::new(queues.clone(), context.clone());
             //tasks.add_task(Box::new(MavTaskPrint::new()));
-            tasks.add_task(Box::new(MavTaskHealth::new()));
-            tasks.add_task(Box::new(MavTaskLla::new()));
-            tasks.add_task(Box::new(MavTaskLocalNed::new()));
+            tasks.add_task(Box::new(MavTaskHealth::new(health_debounce)));
+            tasks.add_task(Box::new(MavTaskHeartbeat::new()));
+            tasks.add_task(Box::new(MavTaskLla::new(telemetry_output)));
+            tasks.add_task(Box::new(MavTaskLocalNed::new(telemetry_output)));
             tasks.add_task(Box::new(MavTaskStatusText::new()));
-            tasks.add_task(Box::new(MavTaskSend::new()));
+            tasks.add_task(Box::new(MavTaskSend::new(command_rate_limit)));
             tasks.start()
     });
 
@@ -1,15 +1,19 @@
-use log::{debug, info};
+use std::{sync::Mutex, time::Instant};
+
+use log::{debug, info, warn};
 
 use crate::{
-    common::{context::QuadAppContext, mavlink_helpers::EkfStatus, state::{LLA, NED}},
+    common::{context::QuadAppContext, health::{HealthDebounce, HealthDebounceConfig, HealthStatus}, mavlink_helpers::EkfStatus},
     link::{mav_queues::MavlinkMessageType, tasks::MavTaskTrait},
 };
 
-pub struct MavTaskHealth {}
+pub struct MavTaskHealth {
+    debounce: Mutex<HealthDebounce>,
+}
 
 impl MavTaskHealth {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(config: HealthDebounceConfig) -> Self {
+        Self { debounce: Mutex::new(HealthDebounce::new(config)) }
     }
 }
 
@@ -25,11 +29,24 @@ impl MavTaskTrait for MavTaskHealth {
             }
             _ => return Ok(()),
         };
-    
+
         let mut state = context.state.write().unwrap();
         let efk_status = EkfStatus::from_flags(res_ekf_status_report.flags);
         state.ekf_status = efk_status;
         debug!("MavTaskHealth // Updated EKF status: {:?}", state.ekf_status);
+
+        let health_status = self
+            .debounce
+            .lock()
+            .unwrap()
+            .update(state.ekf_status.is_healthy().is_ok(), Instant::now());
+        if health_status != state.health_status {
+            match health_status {
+                HealthStatus::Flapping => warn!("MavTaskHealth // Health is FLAPPING"),
+                _ => info!("MavTaskHealth // Health {:?} -> {:?}", state.health_status, health_status),
+            }
+            state.health_status = health_status;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,171 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use mavlink::ardupilotmega::{HEARTBEAT_DATA, MavAutopilot, MavModeFlag};
+
+use crate::{
+    common::{context::QuadAppContext, state::QuadAppState},
+    link::{mav_mode::ArduMode, mav_queues::MavlinkMessageType, tasks::MavTaskTrait},
+};
+
+#[derive(Debug, Clone)]
+pub enum VehicleEvent {
+    ModeChanged { from: Option<ArduMode>, to: ArduMode, timestamp_ms: u128 },
+    ArmedChanged { armed: bool, timestamp_ms: u128 },
+}
+
+/// Decodes the autopilot HEARTBEAT into the current mode / armed state and emits an event on change
+pub struct MavTaskHeartbeat {}
+
+impl MavTaskHeartbeat {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Applies one autopilot heartbeat to the state, returns what changed
+fn update_state(state: &mut QuadAppState, heartbeat: &HEARTBEAT_DATA, timestamp_ms: u128) -> Vec<VehicleEvent> {
+    let mut events = Vec::new();
+    match ArduMode::from_u32(heartbeat.custom_mode) {
+        Some(mode) if state.mode != Some(mode) => {
+            events.push(VehicleEvent::ModeChanged { from: state.mode, to: mode, timestamp_ms });
+            state.mode = Some(mode);
+        }
+        Some(_) => {}
+        None => warn!("MavTaskHeartbeat // Unknown custom mode {}", heartbeat.custom_mode),
+    }
+
+    let armed = heartbeat.base_mode.intersects(MavModeFlag::MAV_MODE_FLAG_SAFETY_ARMED);
+    if armed != state.armed {
+        events.push(VehicleEvent::ArmedChanged { armed, timestamp_ms });
+        state.armed = armed;
+        // Each flight gets a fresh trail
+        if armed {
+            state.clear_ned_history();
+        }
+    }
+    events
+}
+
+impl MavTaskTrait for MavTaskHeartbeat {
+    fn handle_mavlink_message(
+        &self,
+        context: &QuadAppContext,
+        message: MavlinkMessageType,
+    ) -> Result<(), anyhow::Error> {
+        let heartbeat = match message {
+            MavlinkMessageType::HEARTBEAT(heartbeat_data) => heartbeat_data,
+            _ => return Ok(()),
+        };
+        // GCS and companion heartbeats carry no vehicle mode
+        if heartbeat.autopilot == MavAutopilot::MAV_AUTOPILOT_INVALID {
+            return Ok(());
+        }
+
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let events = update_state(&mut context.state.write().unwrap(), &heartbeat, timestamp_ms);
+
+        let mut log_rerun = context.log_rerun.lock().unwrap();
+        for event in events {
+            info!("MavTaskHeartbeat // {:?}", event);
+            match event {
+                VehicleEvent::ModeChanged { from, to, timestamp_ms } => {
+                    let from = from.map(|mode| mode.to_string()).unwrap_or("None".to_string());
+                    log_rerun.log_event(
+                        "mavlink/events/mode",
+                        &format!("{} -> {} @ {}", from, to.to_string(), timestamp_ms),
+                    );
+                }
+                VehicleEvent::ArmedChanged { armed, timestamp_ms } => {
+                    let armed = if armed { "ARMED" } else { "DISARMED" };
+                    log_rerun.log_event("mavlink/events/armed", &format!("{} @ {}", armed, timestamp_ms));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{log_rerun::RerunSink, state::NED};
+
+    fn heartbeat(mode: ArduMode, armed: bool) -> MavlinkMessageType {
+        let base_mode = if armed { MavModeFlag::MAV_MODE_FLAG_SAFETY_ARMED } else { MavModeFlag::empty() };
+        MavlinkMessageType::HEARTBEAT(HEARTBEAT_DATA {
+            custom_mode: mode.to_u32(),
+            autopilot: MavAutopilot::MAV_AUTOPILOT_ARDUPILOTMEGA,
+            base_mode: base_mode | MavModeFlag::MAV_MODE_FLAG_CUSTOM_MODE_ENABLED,
+            ..Default::default()
+        })
+    }
+
+    fn events(state: &mut QuadAppState, message: MavlinkMessageType) -> Vec<VehicleEvent> {
+        let MavlinkMessageType::HEARTBEAT(heartbeat) = message else {
+            panic!("Expected HEARTBEAT");
+        };
+        update_state(state, &heartbeat, 0)
+    }
+
+    #[test]
+    fn mode_change_fires_one_event() {
+        let mut state = QuadAppState::new();
+        let fired = events(&mut state, heartbeat(ArduMode::Stabilize, false));
+        assert!(matches!(fired[..], [VehicleEvent::ModeChanged { from: None, to: ArduMode::Stabilize, .. }]));
+
+        let fired = events(&mut state, heartbeat(ArduMode::Guided, false));
+        assert!(matches!(
+            fired[..],
+            [VehicleEvent::ModeChanged { from: Some(ArduMode::Stabilize), to: ArduMode::Guided, .. }]
+        ));
+        assert_eq!(state.mode, Some(ArduMode::Guided));
+    }
+
+    #[test]
+    fn repeated_heartbeat_fires_nothing() {
+        let mut state = QuadAppState::new();
+        assert_eq!(events(&mut state, heartbeat(ArduMode::Guided, true)).len(), 2);
+        for _ in 0..5 {
+            assert!(events(&mut state, heartbeat(ArduMode::Guided, true)).is_empty());
+        }
+    }
+
+    #[test]
+    fn arming_clears_ned_history() {
+        let mut state = QuadAppState::new();
+        events(&mut state, heartbeat(ArduMode::Guided, false));
+        state.record_ned(NED::new(1.0, 0.0, 0.0));
+        state.record_ned(NED::new(2.0, 0.0, 0.0));
+        let generation = state.ned_history_generation;
+
+        let fired = events(&mut state, heartbeat(ArduMode::Guided, true));
+        assert!(matches!(fired[..], [VehicleEvent::ArmedChanged { armed: true, .. }]));
+        assert!(state.armed);
+        assert!(state.ned_history.is_empty());
+        assert_eq!(state.ned_history_generation, generation + 1);
+
+        // Disarming keeps the trail of the flight that just ended
+        state.record_ned(NED::new(3.0, 0.0, 0.0));
+        let fired = events(&mut state, heartbeat(ArduMode::Guided, false));
+        assert!(matches!(fired[..], [VehicleEvent::ArmedChanged { armed: false, .. }]));
+        assert_eq!(state.ned_history.len(), 1);
+    }
+
+    #[test]
+    fn ignores_heartbeats_without_an_autopilot() {
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut message = heartbeat(ArduMode::Guided, true);
+        if let MavlinkMessageType::HEARTBEAT(data) = &mut message {
+            data.autopilot = MavAutopilot::MAV_AUTOPILOT_INVALID;
+        }
+        MavTaskHeartbeat::new().handle_mavlink_message(&context, message).unwrap();
+        let state = context.state.read().unwrap();
+        assert_eq!(state.mode, None);
+        assert!(!state.armed);
+
+        drop(state);
+        MavTaskHeartbeat::new().handle_mavlink_message(&context, heartbeat(ArduMode::Guided, true)).unwrap();
+        assert_eq!(context.state.read().unwrap().mode, Some(ArduMode::Guided));
+    }
+}
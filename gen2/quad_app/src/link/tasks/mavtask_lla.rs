@@ -1,15 +1,17 @@
 use log::{debug, info};
 
 use crate::{
-    common::{context::QuadAppContext, state::{LLA, NED}},
+    common::{context::QuadAppContext, state::LLA, telemetry_output::TelemetryOutput},
     link::{mav_queues::MavlinkMessageType, tasks::MavTaskTrait},
 };
 
-pub struct MavTaskLla {}
+pub struct MavTaskLla {
+    output: TelemetryOutput,
+}
 
 impl MavTaskLla {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(output: TelemetryOutput) -> Self {
+        Self { output }
     }
 }
 
@@ -23,6 +25,16 @@ impl MavTaskTrait for MavTaskLla {
             MavlinkMessageType::GLOBAL_POSITION_INT(global_position_int_data) => {
                 global_position_int_data
             }
+            MavlinkMessageType::GPS_GLOBAL_ORIGIN(origin) => {
+                let origin = LLA::new(
+                    origin.latitude as f32 / 1e7,
+                    origin.longitude as f32 / 1e7,
+                    origin.altitude as f32 / 1000.0,
+                );
+                info!("MavTaskLla // EKF origin {:?}", origin);
+                context.state.write().unwrap().lla_origin = Some(origin);
+                return Ok(());
+            }
             _ => return Ok(()),
         };
         let mut state = context.state.write().unwrap();
@@ -32,8 +44,26 @@ impl MavTaskTrait for MavTaskLla {
             altitude: (res_global_position_int.alt as f32) / 1000.0,
         };
         state.record_lla(lla);
-        let log_rerun = context.log_rerun.lock().unwrap();
-        log_rerun.log_lla("mavlink/position/lla", &state.lla_current)?;
+        let mut log_rerun = context.log_rerun.lock().unwrap();
+        // The map view needs degrees, so the geo point is always logged in degrees
+        log_rerun.log_lla("mavlink/position/lla", &state.lla_current);
+        log_rerun.log_scalar(
+            &self.output.angle_topic("mavlink/position/latitude"),
+            self.output.angle(state.lla_current.latitude),
+        );
+        log_rerun.log_scalar(
+            &self.output.angle_topic("mavlink/position/longitude"),
+            self.output.angle(state.lla_current.longitude),
+        );
+        log_rerun.log_scalar(
+            &self.output.length_topic("mavlink/position/altitude"),
+            self.output.length(state.lla_current.altitude),
+        );
+        // GPS position in the EKF frame, to compare against LOCAL_POSITION_NED
+        if let Some(origin) = &state.lla_origin {
+            let gps_ned = state.lla_current.to_ned(origin);
+            log_rerun.log_position(&self.output.position_topic("mavlink/position/gps"), self.output.position_z_up(&gps_ned));
+        }
 
         debug!("MavTaskLla // Received global position int: {:?}", res_global_position_int);
         Ok(())
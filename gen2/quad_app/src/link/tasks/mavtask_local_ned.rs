@@ -1,15 +1,17 @@
 use log::{debug, info};
 
 use crate::{
-    common::{context::QuadAppContext, state::NED},
+    common::{context::QuadAppContext, state::NED, telemetry_output::TelemetryOutput},
     link::{mav_queues::MavlinkMessageType, tasks::MavTaskTrait},
 };
 
-pub struct MavTaskLocalNed {}
+pub struct MavTaskLocalNed {
+    output: TelemetryOutput,
+}
 
 impl MavTaskLocalNed {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(output: TelemetryOutput) -> Self {
+        Self { output }
     }
 }
 
@@ -32,8 +34,11 @@ impl MavTaskTrait for MavTaskLocalNed {
             res_local_position.z,
         );
         state.record_ned(ned_pos);
-        let log_rerun = context.log_rerun.lock().unwrap();
-        log_rerun.log_ned("mavlink/position/ned", &state.ned_current)?;
+        let mut log_rerun = context.log_rerun.lock().unwrap();
+        log_rerun.log_position(
+            &self.output.position_topic("mavlink/position"),
+            self.output.position_z_up(&state.ned_current),
+        );
         debug!("MavTaskLocalNed // Received local position NED: {:?}", res_local_position);
         Ok(())
     }
@@ -1,29 +1,92 @@
-use log::info;
+use std::{sync::Mutex, time::Instant};
 
-use crate::{common::{commands::{QuadAppCommand, QuadAppCommandType}, context::QuadAppContext}, link::{mav_queues::MavQueues, tasks::MavTaskTrait}};
+use log::{info, warn};
 
+use crate::{common::{commands::{QuadAppCommand, QuadAppCommandType}, context::QuadAppContext}, link::{mav_builders::{build_position_target_local_ned, build_velocity_target_local_ned}, mav_queues::MavQueues, mav_rate_limit::{CommandRateLimit, CommandRateLimiter}, tasks::MavTaskTrait}};
 
 
-pub struct MavTaskSend{
 
+pub struct MavTaskSend{
+    limiter: Mutex<CommandRateLimiter>,
 }
 
 impl MavTaskSend{
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(rate_limit: CommandRateLimit) -> Self {
+        Self { limiter: Mutex::new(CommandRateLimiter::new(rate_limit)) }
     }
 }
 
 impl MavTaskTrait for MavTaskSend{  
 
     fn handle_app_command(&self, context: &QuadAppContext, queues: &mut MavQueues, command: &QuadAppCommand) -> Result<(), anyhow::Error>{
-        match &command.cmd_type {
-            QuadAppCommandType::MavlinkRaw(msg) => {
-                info!("SkyCanvas // MavTaskSend // Sending message: {:#?}", msg);
-                queues.send(msg.clone())?;
-                Ok(())
-            }
-            _ => Ok(()),
+        let msg = match &command.cmd_type {
+            QuadAppCommandType::MavlinkRaw(msg) => msg.clone(),
+            QuadAppCommandType::Position(ned, yaw_deg) => build_position_target_local_ned(ned, *yaw_deg),
+            QuadAppCommandType::Velocity(velocity) => build_velocity_target_local_ned(velocity),
+            _ => return Ok(()),
+        };
+        if let Err(reason) = self.limiter.lock().unwrap().check(&msg, Instant::now()) {
+            warn!("SkyCanvas // MavTaskSend // Dropping command, {}", reason);
+            return Ok(());
         }
+        info!("SkyCanvas // MavTaskSend // Sending message: {:#?}", msg);
+        queues.send_outbound(msg)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mavlink::ardupilotmega::{MavMessage, PositionTargetTypemask};
+
+    use super::*;
+    use crate::common::{log_rerun::RerunSink, state::NED};
+
+    fn send(command: QuadAppCommandType) -> MavMessage {
+        let task = MavTaskSend::new(CommandRateLimit::default());
+        let context = QuadAppContext::new("test".into(), RerunSink::Disabled);
+        let mut queues = MavQueues::new();
+        task.handle_app_command(&context, &mut queues, &QuadAppCommand::new(command)).unwrap();
+        queues.recv_outbound().unwrap().expect("nothing was sent")
+    }
+
+    #[test]
+    fn position_sends_a_position_only_target() {
+        let MavMessage::SET_POSITION_TARGET_LOCAL_NED(data) = send(QuadAppCommandType::Position(NED::new(1.0, 2.0, -3.0), None)) else {
+            panic!("Expected SET_POSITION_TARGET_LOCAL_NED");
+        };
+        assert_eq!((data.x, data.y, data.z), (1.0, 2.0, -3.0));
+        let ignored = PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AX_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AY_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_AZ_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE
+            | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_RATE_IGNORE;
+        assert_eq!(data.type_mask, ignored);
+    }
+
+    #[test]
+    fn position_with_yaw_keeps_yaw() {
+        let MavMessage::SET_POSITION_TARGET_LOCAL_NED(data) = send(QuadAppCommandType::Position(NED::new(0.0, 0.0, -2.0), Some(90.0))) else {
+            panic!("Expected SET_POSITION_TARGET_LOCAL_NED");
+        };
+        assert!(!data.type_mask.contains(PositionTargetTypemask::POSITION_TARGET_TYPEMASK_YAW_IGNORE));
+        assert!((data.yaw - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn velocity_sends_a_velocity_only_target() {
+        let MavMessage::SET_POSITION_TARGET_LOCAL_NED(data) = send(QuadAppCommandType::Velocity(NED::new(0.5, 0.0, 0.0))) else {
+            panic!("Expected SET_POSITION_TARGET_LOCAL_NED");
+        };
+        assert_eq!(data.vx, 0.5);
+        assert!(data.type_mask.contains(
+            PositionTargetTypemask::POSITION_TARGET_TYPEMASK_X_IGNORE
+                | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_Y_IGNORE
+                | PositionTargetTypemask::POSITION_TARGET_TYPEMASK_Z_IGNORE
+        ));
+        assert!(!data.type_mask.contains(PositionTargetTypemask::POSITION_TARGET_TYPEMASK_VX_IGNORE));
     }
 }
@@ -14,4 +14,5 @@ pub mod mavtask_print;
 pub mod mavtask_status_text;
 pub mod mavtask_local_ned;
 pub mod mavtask_lla;
-pub mod mavtask_health;
\ No newline at end of file
+pub mod mavtask_health;
+pub mod mavtask_heartbeat;
\ No newline at end of file
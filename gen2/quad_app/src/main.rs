@@ -1,26 +1,180 @@
 mod link;
 mod app;
 mod common;
-use log::info;
-use pretty_env_logger;
+use clap::Parser;
+use log::{LevelFilter, info};
 
 use crate::app::QuadApp;
 use crate::app::app_config::AppConfig;
-use crate::link::{QuadLink, mav_config::MavConfig};
+use crate::app::missions::{mission_hop::{HOP_HEIGHT_M, MissionHop}, mission_pattern::MissionPattern};
+use crate::app::patterns::{PatternConfig, PatternKind};
+use crate::common::geofence::{Geofence, parse_geofence};
+use crate::common::state::NED;
+use crate::common::log_rerun::RerunSink;
+use skycanvas_logging::{LogFormat, init_logging};
+use crate::common::telemetry_output::{OutputAngles, OutputFrame, OutputUnits, TelemetryOutput};
+use crate::link::{QuadLink, mav_config::{MavConfig, MavSigningConfig, parse_message_rate}, mav_reconnect::ExitOrContinue};
+use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
 
+#[derive(Parser)]
+pub struct QuadAppArgs {
+    /// MAVLink link config (YAML), command line flags override it
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Load and validate the config, print it and exit without connecting
+    #[clap(long)]
+    check_config: bool,
+    /// Overrides RUST_LOG when set (error, warn, info, debug, trace)
+    #[clap(long)]
+    log_level: Option<LevelFilter>,
+    #[clap(long, value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+    /// Give up (re)connecting to MAVLink after this many attempts (default: retry forever)
+    #[clap(long)]
+    max_reconnect_attempts: Option<u32>,
+    /// Default: continue
+    #[clap(long, value_enum)]
+    on_reconnect_exhausted: Option<ExitOrContinue>,
+    /// Delay after the first failed connect, doubled on each further failure (default: 500)
+    #[clap(long)]
+    reconnect_backoff_ms: Option<u64>,
+    /// Default: 10000
+    #[clap(long)]
+    reconnect_max_backoff_ms: Option<u64>,
+    /// System id used for outgoing MAVLink messages (default: 255)
+    #[clap(long)]
+    source_system_id: Option<u8>,
+    /// Component id used for outgoing MAVLink messages (default: 0)
+    #[clap(long)]
+    source_component_id: Option<u8>,
+    /// Only accept MAVLink messages from this system id
+    #[clap(long)]
+    recv_system_id: Option<u8>,
+    /// Only accept MAVLink messages from this component id
+    #[clap(long)]
+    recv_component_id: Option<u8>,
+    /// Sign outgoing MAVLink v2 messages with this key (64 hex characters)
+    #[clap(long)]
+    signing_key: Option<String>,
+    #[clap(long, default_value_t = 0)]
+    signing_link_id: u8,
+    /// Accept unsigned incoming messages when signing is enabled
+    #[clap(long)]
+    signing_allow_unsigned: bool,
+    /// Use `disabled` on headless rigs without a rerun viewer
+    #[clap(long, value_enum, default_value_t = RerunSink::Spawn)]
+    rerun_sink: RerunSink,
+    /// Frame for logged position telemetry (default: ned)
+    #[clap(long, value_enum)]
+    output_frame: Option<OutputFrame>,
+    /// Default: metric
+    #[clap(long, value_enum)]
+    units: Option<OutputUnits>,
+    /// Units for logged latitude / longitude (default: degrees)
+    #[clap(long, value_enum)]
+    angles: Option<OutputAngles>,
+    /// Request only these messages, e.g. `--message-rate ATTITUDE=50` (repeatable)
+    #[clap(long = "message-rate", value_parser = parse_message_rate)]
+    message_rates: Vec<(u32, f32)>,
+    /// Fly this pattern around the takeoff point after the hop
+    #[clap(long, value_enum)]
+    pattern: Option<PatternKind>,
+    /// Pattern size in meters (circle radius / grid side)
+    #[clap(long, default_value_t = 4.0)]
+    pattern_size: f32,
+    /// Seconds to hold at each pattern waypoint
+    #[clap(long, default_value_t = 1.0)]
+    pattern_hold: f32,
+    /// Skip waypoints outside this local NED box: MIN_N,MIN_E,MIN_D,MAX_N,MAX_E,MAX_D (m)
+    #[clap(long, value_parser = parse_geofence, allow_hyphen_values = true)]
+    geofence: Option<Geofence>,
+}
+
 fn main() -> Result<(), anyhow::Error> {
-    pretty_env_logger::init();
+    let args = QuadAppArgs::parse();
+    init_logging(args.log_level, args.log_format)?;
     log::info!("SkyCanvas // Main // Starting");
-    run()
+    run(args)
+}
+
+/// Config file (or defaults) with any command line overrides applied
+fn build_mav_config(args: &QuadAppArgs) -> Result<MavConfig, anyhow::Error> {
+    let mut config = match &args.config {
+        Some(path) => MavConfig::load(path)?,
+        None => MavConfig::default(),
+    };
+    if args.max_reconnect_attempts.is_some() {
+        config.reconnect.max_attempts = args.max_reconnect_attempts;
+    }
+    if let Some(on_exhausted) = args.on_reconnect_exhausted {
+        config.reconnect.on_exhausted = on_exhausted;
+    }
+    config.reconnect = config.reconnect.clone().with_backoff(
+        args.reconnect_backoff_ms.unwrap_or(config.reconnect.initial_backoff_ms),
+        args.reconnect_max_backoff_ms.unwrap_or(config.reconnect.max_backoff_ms),
+    );
+    if let Some(source_system_id) = args.source_system_id {
+        config.source_system_id = source_system_id;
+    }
+    if let Some(source_component_id) = args.source_component_id {
+        config.source_component_id = source_component_id;
+    }
+    if args.recv_system_id.is_some() {
+        config.recv_system_id = args.recv_system_id;
+    }
+    if args.recv_component_id.is_some() {
+        config.recv_component_id = args.recv_component_id;
+    }
+    if let Some(key) = &args.signing_key {
+        config.signing = Some(MavSigningConfig::new(key.clone(), args.signing_link_id, args.signing_allow_unsigned));
+    }
+    config.telemetry_output = TelemetryOutput::new(
+        args.output_frame.unwrap_or(config.telemetry_output.frame),
+        args.units.unwrap_or(config.telemetry_output.units),
+        args.angles.unwrap_or(config.telemetry_output.angles),
+    );
+    if !args.message_rates.is_empty() {
+        config.message_rates = args.message_rates.iter().copied().collect();
+    }
+    Ok(config)
+}
+
+fn check_config(config: &MavConfig) -> Result<(), anyhow::Error> {
+    println!("{}", serde_yaml::to_string(&config.redacted())?);
+    validate_config(config)?;
+    info!("SkyCanvas // Main // Config OK");
+    Ok(())
+}
+
+/// Logs every problem, fails if there are any
+fn validate_config(config: &MavConfig) -> Result<(), anyhow::Error> {
+    let problems = config.validate();
+    if problems.is_empty() {
+        return Ok(());
+    }
+    for problem in &problems {
+        log::error!("SkyCanvas // Main // Config: {}", problem);
+    }
+    Err(anyhow::anyhow!("Config has {} problem(s)", problems.len()))
 }
 
-fn run() -> Result<(), anyhow::Error> {
-    let config = MavConfig::default();
-    let mut quad_link = QuadLink::new(config.clone());
-    let context = crate::common::context::QuadAppContext::new("quad_app".to_string());
-    let app_config = AppConfig::new();
+fn run(args: QuadAppArgs) -> Result<(), anyhow::Error> {
+    let config = build_mav_config(&args)?;
+    if args.check_config {
+        return check_config(&config);
+    }
+    validate_config(&config)?;
+    let mut quad_link = QuadLink::new(config.clone())?;
+    let context = crate::common::context::QuadAppContext::new("quad_app".to_string(), args.rerun_sink);
+    let mut app_config = AppConfig::new();
+    app_config.geofence = args.geofence.clone();
+    app_config.enqueue_mission(Box::new(MissionHop::new()));
+    if let Some(pattern) = args.pattern {
+        let pattern_config = PatternConfig::new(NED::new(0.0, 0.0, -HOP_HEIGHT_M), args.pattern_size, args.pattern_hold);
+        app_config.enqueue_mission(Box::new(MissionPattern::new(pattern.build(), pattern_config)));
+    }
     let mut app = QuadApp::new(app_config);
 
     let context_clone = context.clone();
@@ -1,6 +1,7 @@
 use clap::Parser;
-use log::info;
+use log::{LevelFilter, info};
 mod shell;
+use skycanvas_logging::{LogFormat, init_logging};
 use crate::shell::Shell;
 #[derive(Parser)]
 pub enum DockerCommand {
@@ -21,12 +22,17 @@ pub enum RepoCliCommand {
 pub struct RepoCliArgs {
     #[clap(subcommand)]
     command: RepoCliCommand,
+    /// Overrides RUST_LOG when set (error, warn, info, debug, trace)
+    #[clap(long, global = true)]
+    log_level: Option<LevelFilter>,
+    #[clap(long, global = true, value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
 }
 
-fn main() {
-    pretty_env_logger::init();
-    info!("Starting Repo CLI...");
+fn main() -> Result<(), anyhow::Error> {
     let args = RepoCliArgs::parse();
+    init_logging(args.log_level, args.log_format)?;
+    info!("Starting Repo CLI...");
     
     // Get the crate via CARGO_MANIFEST_DIR
     let crate_root = std::env::var("CARGO_MANIFEST_DIR").unwrap();
@@ -61,4 +67,5 @@ fn main() {
             }
         },
     }
-}
\ No newline at end of file
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use log::LevelFilter;
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+/// Initialise the global logger.
+///
+/// `RUST_LOG` is honoured when set; an explicit `level` (from `--log-level`)
+/// takes precedence over it. `LogFormat::Json` emits one JSON object per line
+/// for log aggregation.
+pub fn init_logging(level: Option<LevelFilter>, format: LogFormat) -> Result<(), anyhow::Error> {
+    let mut builder = pretty_env_logger::formatted_builder();
+    if let Ok(filters) = std::env::var("RUST_LOG") {
+        builder.parse_filters(&filters);
+    }
+    if let Some(level) = level {
+        builder.filter_level(level);
+    }
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = serde_json::json!({
+                "timestamp": buf.timestamp_millis().to_string(),
+                "level": record.level().to_string(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.try_init()?;
+    Ok(())
+}